@@ -1,17 +1,18 @@
-use crate::frs::avatars::{load_avatars, save_avatars, get_random_emoji_for_name};
+use crate::frs::avatars::get_random_emoji_for_name;
 use crate::frs::emojis::{preview_emojis, search_emojis};
 use serde_json::json;
 use std::io::{self, Write};
 use colored::*;
 use crate::renderer::list::render_list;
+use crate::store::{FurStore, StoreError};
 
-pub fn run_avatar() {
-    let avatars = load_avatars();
+pub fn run_avatar(store: &FurStore) -> Result<(), StoreError> {
+    let avatars = store.load_avatars()?;
 
     if let Some(map) = avatars.as_object() {
         if map.is_empty() {
             println!("(no avatars yet)");
-            return;
+            return Ok(());
         }
 
         let mut rows = Vec::new();
@@ -31,13 +32,15 @@ pub fn run_avatar() {
 
         render_list("Avatars", &["Role", "Emoji"], rows, active_idx);
     }
+    Ok(())
 }
 
 
 
 /// `fur avatar new` → onboarding wizard
-pub fn run_avatar_new() {
-    let mut avatars = load_avatars();
+pub fn run_avatar_new(store: &FurStore) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let mut avatars = store.load_avatars()?;
 
     println!("\n{}", "== Create Avatar ==".bright_magenta().bold());
     println!(
@@ -137,8 +140,9 @@ pub fn run_avatar_new() {
         println!("[OK] Other avatar '{}' created with emoji '{}'", other_name, emoji);
     }
 
-    save_avatars(&avatars);
+    store.save_avatars(&avatars)?;
     println!("✅ Avatar creation complete. Use `fur avatar --view` to list all avatars.");
+    Ok(())
 }
 
 
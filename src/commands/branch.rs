@@ -0,0 +1,37 @@
+use clap::Parser;
+use colored::*;
+
+use crate::store::{short_id, FurStore, OpenBranch, Position, StoreError};
+
+/// Arguments for the `branch` subcommand
+#[derive(Parser, Debug)]
+pub struct BranchArgs {
+    /// Message to branch from (id, prefix, HEAD~N, root); defaults to the current message
+    pub msg: Option<String>,
+}
+
+/// `fur branch [<msg>]`: open a new branch group under a message. The
+/// next plain `fur jot` starts it and later ones continue it.
+pub fn run_branch(store: &FurStore, args: BranchArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+
+    let thread = store.active_thread(&index)?;
+    let parent_id = store.resolve_message_in(args.msg.as_deref().unwrap_or("HEAD"), &thread)?;
+    let parent = store.load_message(&parent_id)?;
+
+    index.current_message = Some(parent_id.clone());
+    index.open_branch = Some(OpenBranch { parent: parent_id.clone(), group: None });
+    store.save_index(&index)?;
+    store.append_reflog("branch", before, Position::of(&index), parent.preview())?;
+
+    println!(
+        "🌿 Opened branch {} under {} {}",
+        (parent.branches.len() + 1).to_string().bright_green().bold(),
+        short_id(&parent_id).bright_black(),
+        parent.preview()
+    );
+    println!("{}", "   Following jots land in it; jump elsewhere to leave it.".bright_black());
+    Ok(())
+}
@@ -5,9 +5,10 @@ use chrono::Utc;
 use colored::*;
 
 use crate::commands::jot::{self, JotArgs};
+use crate::store::{FurStore, StoreError};
 
 /// Interactive chat-style jot for long / structured messages
-pub fn run_chat(avatar: Option<String>) {
+pub fn run_chat(store: &FurStore, avatar: Option<String>) -> Result<(), StoreError> {
     println!("{}", "💬 Write / Copy-Paste your Markdown or text below.".bright_cyan());
     println!("{}", "↪ Finish with Ctrl+D (Linux/macOS) or Ctrl+Z then Enter (Windows).".white());
     println!("{}", "↪ Press Ctrl+C to cancel.".white());
@@ -18,7 +19,7 @@ pub fn run_chat(avatar: Option<String>) {
 
     if buffer.trim().is_empty() {
         println!("⚠️ No content provided. Aborting.");
-        return;
+        return Ok(());
     }
 
     // --- Confirm
@@ -28,7 +29,7 @@ pub fn run_chat(avatar: Option<String>) {
     io::stdin().read_line(&mut confirm).unwrap();
     if confirm.trim().eq_ignore_ascii_case("n") {
         println!("❌ Cancelled.");
-        return;
+        return Ok(());
     }
 
     // --- Filename suggestion
@@ -46,7 +47,7 @@ pub fn run_chat(avatar: Option<String>) {
         fs::create_dir_all(parent).ok();
     }
 
-    fs::write(&path, &buffer).expect("❌ Failed to write file");
+    fs::write(&path, &buffer).map_err(|e| StoreError::io(&path, e))?;
     println!("💾 Saved to {}", path.green());
 
     // --- Reuse jot logic to attach to thread
@@ -57,6 +58,7 @@ pub fn run_chat(avatar: Option<String>) {
         markdown: Some(path),
         img: None,
         parent: None,
+        branch: None,
     };
-    jot::run_jot(args);
+    jot::run_jot(store, args)
 }
@@ -0,0 +1,57 @@
+use clap::Parser;
+use colored::*;
+
+use crate::store::{FurStore, StoreError};
+
+/// Arguments for the `doctor` subcommand
+#[derive(Parser, Debug)]
+pub struct DoctorArgs {
+    /// Repair back-links and drop dangling references
+    #[arg(long)]
+    pub fix: bool,
+}
+
+/// Walk the whole `.fur/` graph and report (or repair) inconsistencies.
+pub fn run_doctor(store: &FurStore, args: DoctorArgs) -> Result<(), StoreError> {
+    let _lock = if args.fix { Some(store.lock()?) } else { None };
+
+    println!("{} {}", "🩺 Checking".bright_cyan().bold(), store.root().display());
+    let issues = store.check()?;
+
+    if issues.is_empty() {
+        println!("{}", "✔️ No problems found.".bright_green().bold());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let marker = if issue.is_fixable() { "❌" } else { "⚠️" };
+        println!("{} {}", marker, issue);
+    }
+
+    let fixable = issues.iter().filter(|i| i.is_fixable()).count();
+    println!(
+        "{}",
+        format!("Found {} issue(s), {} fixable.", issues.len(), fixable).bright_yellow().bold()
+    );
+
+    if !args.fix {
+        if fixable > 0 {
+            println!("Run `fur doctor --fix` to repair them.");
+        }
+        return Ok(());
+    }
+
+    let fixed = store.repair(&issues)?;
+    println!("{}", format!("🔧 Repaired {} issue(s).", fixed).bright_green().bold());
+
+    // Repairs can surface follow-up problems (e.g. a subtree that was hidden)
+    let remaining = store.check()?;
+    if !remaining.is_empty() {
+        println!(
+            "{}",
+            format!("⚠️ {} issue(s) remain. Run `fur doctor` again for details.", remaining.len())
+                .yellow()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+use clap::Parser;
+use colored::*;
+
+use crate::store::{short_id, FurStore, StoreError};
+
+/// Arguments for the `edit` subcommand
+#[derive(Parser, Debug)]
+pub struct EditArgs {
+    /// Message id, unique prefix, HEAD, HEAD~N or root
+    pub id: String,
+}
+
+/// Open a message (or its linked markdown) in `$EDITOR` and save the
+/// result, keeping the previous version in its revision history.
+pub fn run_edit(store: &FurStore, args: EditArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let id = store.resolve_message(&args.id)?;
+    let mut msg = store.load_message(&id)?;
+    let original = store.message_content(&msg)?;
+
+    let scratch = env::temp_dir().join(format!("fur-edit-{}.md", short_id(&id)));
+    fs::write(&scratch, &original).map_err(|e| StoreError::io(&scratch, e))?;
+
+    // $VISUAL/$EDITOR may carry arguments, e.g. "code --wait"
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&scratch)
+        .status()
+        .map_err(|e| StoreError::io(program, e));
+    let edited = fs::read_to_string(&scratch).map_err(|e| StoreError::io(&scratch, e));
+    fs::remove_file(&scratch).ok();
+
+    let status = status?;
+    if !status.success() {
+        eprintln!("❌ {} exited with {} — message left unchanged.", program, status);
+        return Ok(());
+    }
+
+    let mut edited = edited?;
+    if msg.markdown.is_none() {
+        // Editors add a trailing newline that plain jots never had
+        edited.truncate(edited.trim_end_matches('\n').len());
+    }
+
+    if store.revise_message(&mut msg, &edited)? {
+        println!(
+            "{} {} (revision {})",
+            "✏️ Updated message".bright_green().bold(),
+            short_id(&id),
+            msg.revisions.len() + 1
+        );
+    } else {
+        println!("No changes.");
+    }
+    Ok(())
+}
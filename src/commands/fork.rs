@@ -1,81 +1,89 @@
-use std::fs;
-use std::path::Path;
-use serde_json::{Value, json};
-use uuid::Uuid;
-use chrono::Utc;
+use crate::store::{short_id, FurStore, Position, StoreError};
 
-pub fn run_fork_from_active(title: Option<String>) {
-    let index_path = Path::new(".fur").join("index.json");
-    let index_data: Value = serde_json::from_str(&fs::read_to_string(index_path).unwrap()).unwrap();
-    let active_thread = index_data["active_thread"]
-        .as_str()
-        .expect("No active thread set");
+pub fn run_fork_from_active(store: &FurStore, title: Option<String>) -> Result<(), StoreError> {
+    let index = store.load_index()?;
+    let active_thread = index.active_thread.ok_or(StoreError::NoActiveThread)?;
 
-    run_fork(active_thread, title);
+    run_fork(store, &active_thread, title)
 }
 
-pub fn run_fork(thread_id: &str, title: Option<String>) {
-    let fur_dir = Path::new(".fur");
-    let threads_dir = fur_dir.join("threads");
-    let index_path = fur_dir.join("index.json");
-
-    let old_path = threads_dir.join(format!("{}.json", thread_id));
-    if !old_path.exists() {
-        eprintln!("❌ Thread ID {} does not exist at path {:?}", thread_id, old_path);
-        return;
-    }
+pub fn run_fork(store: &FurStore, thread_id: &str, title: Option<String>) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
 
     // Read old thread
-    let old_data: Value = serde_json::from_str(
-        &fs::read_to_string(&old_path).unwrap()
-    ).unwrap();
-
-    let old_title = old_data["title"].as_str().unwrap_or("Untitled");
-    let new_id = Uuid::new_v4().to_string();
-    let timestamp = Utc::now().to_rfc3339();
+    let old = store.load_thread(thread_id)?;
 
     // Pick fork title
-    let fork_title: String;
     let used_custom_title = title.is_some();
-
-    fork_title = match title {
+    let fork_title = match title {
         Some(custom) => custom,
-        None => format!("Fork of {}", old_title),
+        None => format!("Fork of {}", old.title),
     };
 
-    let messages = old_data["messages"].clone();
-
-    let new_thread = json!({
-        "id": new_id,
-        "title": fork_title,
-        "created_at": timestamp,
-        "forked_from": thread_id,
-        "messages": messages
-    });
-
-    let new_path = threads_dir.join(format!("{}.json", new_id));
-    fs::write(&new_path, serde_json::to_string_pretty(&new_thread).unwrap()).unwrap();
-
-    // Update index.json
-    let mut index_data: Value = serde_json::from_str(
-        &fs::read_to_string(&index_path).unwrap()
-    ).unwrap();
-
-    index_data["active_thread"] = json!(new_id);
-    index_data["threads"].as_array_mut().unwrap().push(json!(new_id));
-
-    fs::write(index_path, serde_json::to_string_pretty(&index_data).unwrap()).unwrap();
-
+    // Every message is copied, so the fork can diverge without touching the original
+    store.begin_op("fork");
+    let (new_thread, copies) = store.fork_thread(thread_id, &fork_title)?;
+
+    // Update index.json (the cursor follows onto its copy)
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+    let cursor = index.current_message.as_ref().and_then(|c| copies.get(c)).cloned();
+    index.threads.push(new_thread.id.clone());
+    store.switch_thread(&mut index, &new_thread.id)?;
+    index.current_message = cursor;
+    store.save_index(&index)?;
+    store.commit_op(fork_title.as_str())?;
+    store.append_reflog("fork", before, Position::of(&index), fork_title.as_str())?;
 
     if used_custom_title {
         println!(
             "🌱 Created fork \"{}\" from {} -- {} → {}",
-            fork_title, old_title, thread_id, new_id
+            fork_title, old.title, thread_id, new_thread.id
         );
     } else {
         println!(
             "🌱 Forked thread from {} -- {} → {}",
-            old_title, thread_id, new_id
+            old.title, thread_id, new_thread.id
         );
     }
+    Ok(())
+}
+
+/// `fur fork --at [<msg>]`: a new thread holding only the lineage that
+/// leads to one message, with the cursor on it.
+pub fn run_fork_at(store: &FurStore, at: Option<String>, title: Option<String>) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let mut index = store.load_index()?;
+
+    let msg_id = match at {
+        Some(prefix) => store.resolve_message(&prefix)?,
+        None => index.current_message.clone().ok_or_else(|| {
+            StoreError::Invalid("No current message to fork from. Pass --at <msg> or jump first.".to_string())
+        })?,
+    };
+    let source = store
+        .thread_containing(&index, &msg_id)
+        .ok_or_else(|| StoreError::MessageNotFound(msg_id.clone()))?;
+
+    let fork_title = title.unwrap_or_else(|| format!("Fork of {}", source.title));
+    store.begin_op("fork");
+    let (new_thread, copies) = store.fork_lineage(&source.id, &msg_id, &fork_title)?;
+
+    let before = Position::of(&index);
+    index.threads.push(new_thread.id.clone());
+    store.switch_thread(&mut index, &new_thread.id)?;
+    index.current_message = copies.get(&msg_id).cloned();
+    store.save_index(&index)?;
+    store.commit_op(fork_title.as_str())?;
+    store.append_reflog("fork", before, Position::of(&index), fork_title.as_str())?;
+
+    println!(
+        "🌱 Forked \"{}\" at {} ({} message(s) of history) -- {} → {}",
+        fork_title,
+        short_id(&msg_id),
+        copies.len(),
+        source.id,
+        new_thread.id
+    );
+    Ok(())
 }
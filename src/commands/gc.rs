@@ -0,0 +1,81 @@
+use clap::Parser;
+use colored::*;
+use std::time::Duration;
+
+use crate::store::{short_id, FurStore, StoreError};
+
+/// Arguments for the `gc` subcommand
+#[derive(Parser, Debug)]
+pub struct GcArgs {
+    /// List what would be deleted without deleting it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Leave anything modified more recently than this alone (e.g. 30m, 2h, 7d, 0)
+    #[arg(long, default_value = "1h", value_parser = parse_grace)]
+    pub grace: Duration,
+}
+
+/// Delete unreachable messages and stale ephemeral threads.
+pub fn run_gc(store: &FurStore, args: GcArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+
+    let report = store.gc(args.grace, args.dry_run)?;
+
+    if report.collected.is_empty() && report.stale_tmp.is_empty() {
+        println!("{}", "✔️ Nothing to collect.".bright_green().bold());
+    }
+
+    let verb = if report.dry_run { "Would delete" } else { "Deleted" };
+    for id in &report.collected {
+        let preview = store.load_message(id).map(|m| m.preview()).unwrap_or_default();
+        println!("🗑️ {} {}", short_id(id).bright_black(), preview);
+    }
+    for path in &report.stale_tmp {
+        println!("🗑️ {}", path.display().to_string().bright_black());
+    }
+
+    if !report.collected.is_empty() || !report.stale_tmp.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "{} {} unreachable message(s) and {} ephemeral file(s).",
+                verb,
+                report.collected.len(),
+                report.stale_tmp.len()
+            )
+            .bright_yellow()
+            .bold()
+        );
+    }
+    if !report.kept_recent.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "⏳ Kept {} unreachable message(s) newer than the grace period.",
+                report.kept_recent.len()
+            )
+            .bright_black()
+        );
+    }
+    Ok(())
+}
+
+/// Parse `90`, `90s`, `15m`, `2h` or `7d` into a Duration.
+fn parse_grace(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let (num, unit) = match raw.find(|c: char| !c.is_ascii_digit()) {
+        Some(pos) => raw.split_at(pos),
+        None => (raw, "s"),
+    };
+    let n: u64 = num.parse().map_err(|_| format!("invalid duration: {}", raw))?;
+    let scale: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("unknown duration unit '{}' (use s, m, h or d)", unit)),
+    };
+    let secs = n.checked_mul(scale).ok_or_else(|| format!("duration too long: {}", raw))?;
+    Ok(Duration::from_secs(secs))
+}
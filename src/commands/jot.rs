@@ -1,11 +1,7 @@
-use std::fs;
-use std::path::Path;
-use serde_json::{json, Value};
 use clap::Parser;
-use chrono::Utc;
-use uuid::Uuid;
 
-use crate::frs::avatars::{load_avatars, resolve_avatar};
+use crate::frs::avatars::resolve_avatar;
+use crate::store::{short_id, FurStore, OpenBranch, Position, StoreError, StoredMessage};
 
 #[derive(Parser, Debug)]
 pub struct JotArgs {
@@ -29,24 +25,29 @@ pub struct JotArgs {
     #[arg(long)]
     pub img: Option<String>,
 
-    /// Parent message ID (optional, for replies)
+    /// Parent message ID, prefix, HEAD, HEAD~N or root (optional, for replies)
     #[arg(long)]
     pub parent: Option<String>,
+
+    /// Add to branch group N (as numbered by `fur status`) under --parent or the current message
+    #[arg(long)]
+    pub branch: Option<usize>,
 }
 
 
-pub fn run_jot(args: JotArgs) {
-    let fur_dir = Path::new(".fur");
-    if !fur_dir.exists() {
-        eprintln!("🚨 .fur/ not found. Run `fur new` first.");
-        return;
-    }
+pub fn run_jot(store: &FurStore, args: JotArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
 
     // Load avatars
-    let avatars = load_avatars();
-    let avatar_map = avatars
-        .as_object()
-        .expect("avatars.json must be a valid JSON object");
+    let avatars = store.load_avatars()?;
+    let avatar_map = avatars.as_object().cloned().unwrap_or_default();
+    let default_avatar = || {
+        avatar_map
+            .get("main")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string()
+    };
 
     // Determine avatar + text
     let (avatar_name, jot_text) = match (&args.avatar, &args.positional_text) {
@@ -55,30 +56,11 @@ pub fn run_jot(args: JotArgs) {
             if avatar_map.contains_key(a) {
                 (a.clone(), args.text.clone())
             } else {
-                let default_avatar = avatar_map
-                    .get("main")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-                (default_avatar, Some(a.clone()))
+                (default_avatar(), Some(a.clone()))
             }
         }
-        (None, Some(t)) => {
-            let default_avatar = avatar_map
-                .get("main")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-            (default_avatar, Some(t.clone()))
-        }
-        (None, None) => {
-            let default_avatar = avatar_map
-                .get("main")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-            (default_avatar, args.text.clone())
-        }
+        (None, Some(t)) => (default_avatar(), Some(t.clone())),
+        (None, None) => (default_avatar(), args.text.clone()),
     };
 
     // Final text resolution (flag overrides positional)
@@ -88,99 +70,88 @@ pub fn run_jot(args: JotArgs) {
     // Optionally: prevent empty jots
     if final_text.is_none() && args.markdown.is_none() {
         eprintln!("🛑 You must provide either text or a markdown file.");
-        return;
+        return Ok(());
     }
 
-    let message_id = Uuid::new_v4().to_string();
-    let timestamp = Utc::now().to_rfc3339();
-
-    let mut message = json!({
-        "id": message_id,
-        "avatar": avatar_name,
-        "timestamp": timestamp,
-        "children": [],
-        "branches": [],
-    });
-
-    if let Some(text) = final_text {
-        message["text"] = json!(text);
-    }
-    if let Some(md) = args.markdown {
-        message["markdown"] = json!(md);
-    }
-    if let Some(img) = args.img {
-        message["attachment"] = json!(img);
-    }
-    if let Some(ref parent_id) = args.parent {
-        message["parent"] = json!(parent_id);
-    }
+    store.begin_op("jot");
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+    let mut thread = store.active_thread(&index)?;
+    let parent = args.parent.as_deref().map(|p| store.resolve_message(p)).transpose()?;
+
+    // Where in the parent it goes: a plain child, or a branch group (new when None)
+    let (parent, group) = match (args.branch, parent) {
+        (Some(0), _) => return Err(StoreError::Invalid("Branches are numbered from 1.".to_string())),
+        (Some(n), parent) => {
+            let parent = parent.or_else(|| index.current_message.clone()).ok_or_else(|| {
+                StoreError::Invalid("No current message to branch from. Pass --parent <msg> or jump first.".to_string())
+            })?;
+            (Some(parent), Some(Some(n - 1)))
+        }
+        (None, Some(parent)) => (Some(parent), None),
+        (None, None) => match store.open_branch(&index) {
+            Some(open) => (Some(open.parent), Some(open.group)),
+            None => (None, None),
+        },
+    };
 
-    // Save message
-    let msg_path = fur_dir.join("messages").join(format!("{}.json", message_id));
-    if let Ok(serialized) = serde_json::to_string_pretty(&message) {
-        let _ = fs::write(msg_path, serialized);
+    // An open branch or the cursor may still point into another thread
+    if let Some(ref parent_id) = parent {
+        if !store.load_tree(&thread).contains_key(parent_id) {
+            if index.open_branch.take().is_some() {
+                store.save_index(&index)?;
+            }
+            return Err(StoreError::Invalid(format!(
+                "Message {} is not in thread \"{}\". Jump to a message in this thread first.",
+                short_id(parent_id),
+                thread.title
+            )));
+        }
     }
 
-    // Load index
-    let index_path = fur_dir.join("index.json");
-    let mut index_data: Value = serde_json::from_str(
-        &fs::read_to_string(&index_path).expect("Cannot read index.json"),
-    )
-    .unwrap();
-
-    let thread_id = index_data["active_thread"]
-        .as_str()
-        .unwrap()
-        .to_string();
-
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-    let mut thread_data: Value =
-        serde_json::from_str(&fs::read_to_string(&thread_path).expect("Cannot read thread"))
-            .unwrap();
+    let mut message = StoredMessage::new(&avatar_name, parent.clone());
+    message.text = final_text;
+    message.markdown = args.markdown.map(|p| store.to_project_path(&p));
+    message.attachment = args.img.map(|p| store.to_project_path(&p));
+    let message_id = message.id.clone();
 
     // Insert message into correct place
-    if let Some(ref parent_id) = args.parent {
+    let mut branch_note = None;
+    if let (Some(parent_id), Some(group)) = (&parent, group) {
+        let group = store.add_to_branch(parent_id, group, &message_id)?;
+        store.save_message(&message)?;
+        index.open_branch = Some(OpenBranch { parent: parent_id.clone(), group: Some(group) });
+        branch_note = Some(format!("🌿 in branch {} of {}", group + 1, short_id(parent_id)));
+    } else if let Some(ref parent_id) = parent {
         // Load parent and attach child
-        let parent_path = fur_dir.join("messages").join(format!("{}.json", parent_id));
-        if let Ok(content) = fs::read_to_string(&parent_path) {
-            if let Ok(mut parent_json) = serde_json::from_str::<Value>(&content) {
-                if let Some(children) = parent_json["children"].as_array_mut() {
-                    children.push(json!(message_id));
-                } else {
-                    parent_json["children"] = json!([message_id]);
-                }
-
-                // Save updated parent
-                if let Ok(serialized) = serde_json::to_string_pretty(&parent_json) {
-                    let _ = fs::write(parent_path, serialized);
-                }
-            }
-        }
+        let mut parent = store.load_message(parent_id)?;
+        parent.children.push(message_id.clone());
+        store.save_message(&message)?;
+        store.save_message(&parent)?;
     } else {
         // Root-level message → append only once
-        if let Some(arr) = thread_data["messages"].as_array_mut() {
-            arr.push(json!(message_id));
-        }
-    }
-
-    // Save updated thread
-    if let Ok(serialized) = serde_json::to_string_pretty(&thread_data) {
-        let _ = fs::write(thread_path, serialized);
+        store.save_message(&message)?;
+        thread.messages.push(message_id.clone());
+        store.save_thread(&thread)?;
     }
 
     // Update index current message
-    index_data["current_message"] = json!(message_id);
-    if let Ok(serialized) = serde_json::to_string_pretty(&index_data) {
-        let _ = fs::write(index_path, serialized);
-    }
+    index.current_message = Some(message_id.clone());
+    store.save_index(&index)?;
+    store.commit_op(message.preview())?;
+    store.append_reflog("jot", before, Position::of(&index), message.preview())?;
 
     // Resolve emoji for display
     let (_, emoji) = resolve_avatar(&avatars, &avatar_name);
     println!(
         "✍️ Message jotted down to thread {}: {} [{}] {}",
-        thread_id,
-        &message_id[..8],
+        thread.id,
+        short_id(&message_id),
         avatar_name,
         emoji
     );
+    if let Some(note) = branch_note {
+        println!("{}", note);
+    }
+    Ok(())
 }
@@ -1,90 +1,159 @@
-use std::fs;
-use std::path::Path;
 use clap::Args;
-use serde_json::Value;
+
+use crate::store::navigate::{compute_branch_label, find_branch, first_leaf, siblings, Tree};
+use crate::store::{short_id, FurStore, Index, Position, StoreError};
 
 /// JumpArgs allow specifying relative or absolute jumps
 #[derive(Args, Debug)]
+#[group(id = "target", multiple = false)]
 pub struct JumpArgs {
+    /// Go up N parents
     #[arg(short, long)]
     pub past: Option<usize>,
 
+    /// Go to the Nth child (0-based) of the current message
     #[arg(short, long)]
     pub child: Option<usize>,
 
+    /// Go to the next sibling
+    #[arg(long)]
+    pub next: bool,
+
+    /// Go to the previous sibling
+    #[arg(long)]
+    pub prev: bool,
+
+    /// Go to the root of the current message's lineage
+    #[arg(long)]
+    pub root: bool,
+
+    /// Follow first children down to a leaf
+    #[arg(long)]
+    pub leaf: bool,
+
+    /// Go to a branch by label, as shown by `fur status` (e.g. 1.2)
+    #[arg(short, long)]
+    pub branch: Option<String>,
+
+    /// Message to jump to: id, unique prefix, HEAD~N or root
     #[arg(short, long)]
     pub id: Option<String>,
+
+    /// Jump to a bookmark set with `fur mark`
+    #[arg(short, long)]
+    pub mark: Option<String>,
+
+    /// Return to where entry N of `fur reflog` left the cursor (0 = latest)
+    #[arg(long)]
+    pub reflog: Option<usize>,
 }
 
-pub fn run_jump(args: JumpArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let index_path = Path::new(".fur/index.json");
-    let index_data = fs::read_to_string(index_path).expect("❌ Couldn't read index.json");
-    let mut index: Value = serde_json::from_str(&index_data).unwrap();
-
-    let thread_id = index["active_thread"].as_str().unwrap();
-    let thread_path = Path::new(".fur/threads").join(format!("{}.json", thread_id));
-    let thread_data = fs::read_to_string(thread_path).expect("❌ Couldn't read thread file");
-    let thread: Value = serde_json::from_str(&thread_data).unwrap();
-
-    let current_id = index["current_message"].as_str().unwrap_or_default();
-
-    let messages = thread["messages"].as_array().unwrap();
-
-    // Locate current message
-    let current_msg_id = current_id;
-    let current_msg = messages
-        .iter()
-        .find_map(|id| {
-            if id.as_str()? == current_msg_id {
-                let msg_path = Path::new(".fur/messages").join(format!("{}.json", current_msg_id));
-                let msg_data = fs::read_to_string(msg_path).ok()?;
-                serde_json::from_str::<Value>(&msg_data).ok()
-            } else {
-                None
+pub fn run_jump(store: &FurStore, args: JumpArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+
+    // Handle jump --reflog (may switch threads)
+    if let Some(n) = args.reflog {
+        let entries = store.load_reflog()?;
+        let entry = entries
+            .get(n)
+            .ok_or_else(|| StoreError::Invalid(format!("No reflog entry {} ({} recorded).", n, entries.len())))?;
+        let Some(tid) = entry.after.thread.clone().filter(|t| index.threads.contains(t)) else {
+            return Err(StoreError::Invalid(format!("The thread of reflog entry {} no longer exists.", n)));
+        };
+        if let Some(ref mid) = entry.after.message {
+            if !store.message_exists(mid) {
+                return Err(StoreError::Invalid(format!(
+                    "Message {} of reflog entry {} no longer exists (see `fur restore`).",
+                    short_id(mid),
+                    n
+                )));
             }
-        });
+        }
+        store.switch_thread(&mut index, &tid)?;
+        index.current_message = entry.after.message.clone();
+        store.save_index(&index)?;
+        store.append_reflog("jump", before, Position::of(&index), format!("reflog {}", n))?;
+        println!(
+            "↩️ Returned to reflog entry {} ({} {})",
+            n,
+            entry.action,
+            entry.after.message.as_deref().map(short_id).unwrap_or("-")
+        );
+        return Ok(());
+    }
 
-    if current_msg.is_none() {
-        eprintln!("❌ Current message not found in thread.");
+    // Handle jump --mark (may switch threads for a global mark)
+    if let Some(ref name) = args.mark {
+        let active = store.active_thread(&index).ok();
+        let (target, _) = store
+            .find_mark(&index, active.as_ref(), name)
+            .ok_or_else(|| StoreError::Invalid(format!("No mark named '{}'", name)))?;
+        let owner = store
+            .thread_containing(&index, &target)
+            .ok_or_else(|| StoreError::MessageNotFound(target.clone()))?;
+        store.switch_thread(&mut index, &owner.id)?;
+        set_current(store, &mut index, &before, &target)?;
+        println!("🔖 Jumped to mark '{}' → {} in \"{}\"", name, short_id(&target), owner.title);
         return Ok(());
     }
-    let current = current_msg.unwrap();
 
-    // Handle jump --past
-    if let Some(n) = args.past {
-        let mut current_id = current["id"].as_str().unwrap_or_default().to_string();
-        let mut jumped = 0;
+    let thread = store.active_thread(&index)?;
+    let tree = store.load_tree(&thread);
 
-        while jumped < n {
-            // println!("🔍 Loop iteration {} — current_id: {}", jumped, current_id);
+    // Handle jump --id (anywhere in the thread's tree)
+    if let Some(ref spec) = args.id {
+        let target_id = store.resolve_message_in(spec, &thread)?;
+        set_current(store, &mut index, &before, &target_id)?;
+        println!("🎯 Jumped directly to message ID {}", target_id);
+        return Ok(());
+    }
 
-            let msg_path = Path::new(".fur/messages").join(format!("{}.json", current_id));
-            let msg_data = fs::read_to_string(&msg_path);
-            if msg_data.is_err() {
-                eprintln!("❌ Failed to load message: {}", current_id);
-                return Ok(());
+    // --root and --branch work without a current message: start at the first root
+    let current = index
+        .current_message
+        .as_ref()
+        .and_then(|id| tree.get(id))
+        .map(|m| m.id.clone());
+    let lineage_root = match current {
+        Some(ref id) => store.lineage(id)?.into_iter().next(),
+        None => thread.messages.first().cloned(),
+    };
+
+    if args.root || args.branch.is_some() {
+        let root = lineage_root.ok_or_else(|| StoreError::Invalid("This thread has no messages yet.".to_string()))?;
+        let target = match args.branch {
+            Some(ref spec) => {
+                // The current lineage first, then the other roots
+                let mut roots = vec![root.clone()];
+                roots.extend(thread.messages.iter().filter(|r| **r != root).cloned());
+                find_branch(&roots, spec, &tree)?
             }
+            None => root,
+        };
+        set_current(store, &mut index, &before, &target)?;
+        println!(
+            "🌳 Jumped to {} {} {}",
+            compute_branch_label(&target, &tree),
+            short_id(&target),
+            preview(&tree, &target)
+        );
+        return Ok(());
+    }
 
-            let msg_json: Value = serde_json::from_str(&msg_data.unwrap()).unwrap();
-
-            // let parent_raw = &msg_json["parent"];
-            // println!("   ↪️ parent field raw: {}", parent_raw);
-
-            match msg_json["parent"].as_str() {
-                Some(pid) if !pid.is_empty() => {
-                    let in_thread = thread["messages"]
-                        .as_array()
-                        .unwrap_or(&vec![])
-                        .iter()
-                        .any(|val| val.as_str() == Some(pid));
+    let current = current
+        .and_then(|id| tree.get(&id))
+        .ok_or_else(|| StoreError::Invalid("Current message not found in thread.".to_string()))?;
 
-                    if !in_thread {
-                        println!("\x1b[91m📜 You've reached the origin of this thread. No earlier messages exist.\x1b[0m");
-                        println!("\x1b[93m🌱 To start a new conversation, run:\n    fur new \"Title of your new thread\"\x1b[0m");
-                        return Ok(());
-                    }
+    // Handle jump --past
+    if let Some(n) = args.past {
+        let mut current_id = current.id.clone();
+        let mut jumped = 0;
 
-                    // println!("   🧬 Jumping to parent_id: {}", pid);
+        while jumped < n {
+            match tree.get(&current_id).and_then(|m| m.parent.as_deref()) {
+                Some(pid) if tree.contains_key(pid) => {
                     current_id = pid.to_string();
                     jumped += 1;
                 }
@@ -96,8 +165,7 @@ pub fn run_jump(args: JumpArgs) -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        index["current_message"] = Value::String(current_id.clone());
-        fs::write(index_path, serde_json::to_string_pretty(&index).unwrap()).unwrap();
+        set_current(store, &mut index, &before, &current_id)?;
         println!(
             "⏪ Jumped back {} message{} to {}",
             jumped,
@@ -107,49 +175,55 @@ pub fn run_jump(args: JumpArgs) -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
-
     // Handle jump --child
     if let Some(n) = args.child {
-        let current_id = current["id"].as_str().unwrap_or_default();
-
-        let children: Vec<String> = messages.iter().filter_map(|msg_id| {
-            let msg_path = Path::new(".fur/messages").join(format!("{}.json", msg_id.as_str()?));
-            let msg_data = fs::read_to_string(msg_path).ok()?;
-            let msg_json: Value = serde_json::from_str(&msg_data).ok()?;
-            if msg_json["parent"].as_str()? == current_id {
-                Some(msg_json["id"].as_str()?.to_string())
-            } else {
-                None
+        let children = current.child_ids();
+        let child_id = children
+            .get(n)
+            .ok_or_else(|| StoreError::Invalid(format!("No such child at index {}", n)))?;
+        set_current(store, &mut index, &before, child_id)?;
+        println!("⏩ Jumped to child [{}]: {}", n, child_id);
+        return Ok(());
+    }
+
+    // Handle jump --next / --prev
+    if args.next || args.prev {
+        let sibs = siblings(&current.id, &tree, &thread);
+        let pos = sibs.iter().position(|s| s == &current.id).unwrap_or(0);
+        let target = if args.next { sibs.get(pos + 1) } else { pos.checked_sub(1).and_then(|p| sibs.get(p)) };
+        match target {
+            Some(id) => {
+                set_current(store, &mut index, &before, id)?;
+                let arrow = if args.next { "➡️ Next" } else { "⬅️ Previous" };
+                println!("{} sibling: {} {}", arrow, short_id(id), preview(&tree, id));
             }
-        }).collect();
-
-        if let Some(child_id) = children.get(n) {
-            index["current_message"] = Value::String(child_id.to_string());
-            fs::write(index_path, serde_json::to_string_pretty(&index).unwrap()).unwrap();
-            println!("⏩ Jumped to child [{}]: {}", n, child_id);
-            return Ok(());
-        } else {
-            eprintln!("❌ No such child at index {}", n);
-            return Ok(());
+            None => eprintln!("❌ No {} sibling.", if args.next { "next" } else { "previous" }),
         }
-
+        return Ok(());
     }
 
-    // Handle jump --id
-    if let Some(ref target_id) = args.id {
-        if messages.iter().any(|m| m["id"].as_str() == Some(&target_id)) {
-            index["current_message"] = Value::String(target_id.to_string());
-            fs::write(index_path, serde_json::to_string_pretty(&index).unwrap()).unwrap();
-            println!("🎯 Jumped directly to message ID {}", target_id);
-            return Ok(());
-        } else {
-            eprintln!("❌ Message ID not found: {}", target_id);
-            return Ok(());
-        }
+    // Handle jump --leaf
+    if args.leaf {
+        let leaf = first_leaf(&current.id, &tree);
+        set_current(store, &mut index, &before, &leaf)?;
+        println!("🍃 Jumped to leaf {} {}", short_id(&leaf), preview(&tree, &leaf));
+        return Ok(());
     }
 
-    eprintln!("❗ No jump argument provided. Use --past, --child, or --id.");
-    Ok(())
+    Err(StoreError::Invalid(
+        "No jump argument provided. Use --past, --child, --next, --prev, --root, --leaf, --branch, --id, --mark or --reflog."
+            .to_string(),
+    ))
+}
 
+/// Move the cursor, save the index and record the move in the reflog.
+fn set_current(store: &FurStore, index: &mut Index, before: &Position, msg_id: &str) -> Result<(), StoreError> {
+    index.current_message = Some(msg_id.to_string());
+    store.save_index(index)?;
+    let detail = store.load_message(msg_id).map(|m| m.preview()).unwrap_or_default();
+    store.append_reflog("jump", before.clone(), Position::of(index), detail)
+}
 
+fn preview(tree: &Tree, msg_id: &str) -> String {
+    tree.get(msg_id).map(|m| m.preview()).unwrap_or_default()
 }
@@ -0,0 +1,73 @@
+use clap::Parser;
+use colored::*;
+
+use crate::renderer::list::render_list;
+use crate::store::{short_id, FurStore, MarkScope, StoreError};
+
+/// Arguments for the `mark` subcommand
+#[derive(Parser, Debug)]
+pub struct MarkArgs {
+    /// Bookmark name
+    pub name: String,
+
+    /// Message to mark (id, prefix, HEAD~N, root); defaults to the current message
+    pub msg: Option<String>,
+
+    /// Store the mark globally instead of in the message's thread
+    #[arg(short, long)]
+    pub global: bool,
+
+    /// Remove the mark instead of setting it
+    #[arg(short, long, conflicts_with = "msg")]
+    pub delete: bool,
+}
+
+/// `fur mark <name> [<msg>]`: bookmark a message.
+pub fn run_mark(store: &FurStore, args: MarkArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let scope = if args.global { MarkScope::Global } else { MarkScope::Thread };
+
+    if args.delete {
+        if store.remove_mark(&args.name, scope)? {
+            println!("🗑️ Removed mark '{}'", args.name);
+        } else {
+            eprintln!("❌ No mark named '{}'", args.name);
+        }
+        return Ok(());
+    }
+
+    let msg_id = store.resolve_message(args.msg.as_deref().unwrap_or("HEAD"))?;
+    store.set_mark(&args.name, &msg_id, scope)?;
+    let preview = store.load_message(&msg_id).map(|m| m.preview()).unwrap_or_default();
+    println!(
+        "🔖 {} '{}' → {} {}",
+        if args.global { "Global mark" } else { "Mark" },
+        args.name.bright_magenta().bold(),
+        short_id(&msg_id).bright_black(),
+        preview
+    );
+    Ok(())
+}
+
+/// `fur marks`: list the active thread's bookmarks and the global ones.
+pub fn run_marks(store: &FurStore) -> Result<(), StoreError> {
+    let index = store.load_index()?;
+    let thread = store.active_thread(&index).ok();
+
+    let mut rows = Vec::new();
+    let thread_marks = thread.iter().flat_map(|t| t.marks.iter().map(|m| (m, "thread")));
+    for ((name, id), scope) in thread_marks.chain(index.marks.iter().map(|m| (m, "global"))) {
+        let preview = match store.load_message(id) {
+            Ok(m) => m.preview(),
+            Err(_) => "(missing)".to_string(),
+        };
+        rows.push(vec![name.clone(), scope.to_string(), short_id(id).to_string(), preview]);
+    }
+
+    if rows.is_empty() {
+        println!("(no marks yet — add one with `fur mark <name>`)");
+        return Ok(());
+    }
+    render_list("Marks", &["Name", "Scope", "ID", "Message"], rows, None);
+    Ok(())
+}
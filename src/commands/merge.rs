@@ -0,0 +1,81 @@
+use clap::Parser;
+use colored::*;
+
+use crate::store::{short_id, FurStore, StoreError};
+
+/// Arguments for the `merge` subcommand
+#[derive(Parser, Debug)]
+pub struct MergeArgs {
+    /// Thread to take messages from (ID or prefix)
+    pub thread: String,
+
+    /// Thread to graft into (defaults to the active thread)
+    #[arg(long)]
+    pub into: Option<String>,
+
+    /// Target message to graft under (defaults to the current message when
+    /// it is in the target thread, else the grafts become root messages)
+    #[arg(long)]
+    pub at: Option<String>,
+
+    /// Only graft this message's subtree from the source thread
+    #[arg(long)]
+    pub from: Option<String>,
+}
+
+/// Bring another thread's messages (or one subtree) into a thread as new branches.
+pub fn run_merge(store: &FurStore, args: MergeArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let index = store.load_index()?;
+
+    let source = store.resolve_thread(&index, &args.thread)?;
+    let target = match args.into {
+        Some(ref t) => store.resolve_thread(&index, t)?,
+        None => index.active_thread.clone().ok_or(StoreError::NoActiveThread)?,
+    };
+    if source == target {
+        return Err(StoreError::Invalid("Can't merge a thread into itself.".to_string()));
+    }
+
+    let from = match args.from {
+        Some(ref m) => Some(store.resolve_message_in(m, &store.load_thread(&source)?)?),
+        None => None,
+    };
+    let at = match args.at {
+        Some(ref m) => Some(store.resolve_message_in(m, &store.load_thread(&target)?)?),
+        None => index.current_message.clone().filter(|c| {
+            store
+                .load_thread(&target)
+                .map(|t| store.load_tree(&t).contains_key(c))
+                .unwrap_or(false)
+        }),
+    };
+
+    let report = store.merge_thread(&source, &target, from.as_deref(), at.as_deref())?;
+
+    if report.grafted.is_empty() {
+        println!("✔️ Nothing to merge: every message is already in the target ({} skipped).", report.skipped);
+        return Ok(());
+    }
+    for (anchor, ids) in &report.grafted {
+        let place = match anchor {
+            Some(a) => format!("under {}", short_id(a)),
+            None => "at the root".to_string(),
+        };
+        let shown: Vec<&str> = ids.iter().map(|id| short_id(id)).collect();
+        println!("🌿 Grafted [{}] {}", shown.join(", ").bright_yellow(), place);
+    }
+    println!(
+        "{}",
+        format!(
+            "🔀 Merged {} message(s) from {} into {} ({} already present, skipped).",
+            report.copied,
+            short_id(&source),
+            short_id(&target),
+            report.skipped
+        )
+        .bright_green()
+        .bold()
+    );
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+use clap::Parser;
+use colored::*;
+
+use crate::store::{FurStore, StoreError};
+
+/// Arguments for the `migrate` subcommand
+#[derive(Parser, Debug)]
+pub struct MigrateArgs {
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Upgrade the store at `root` to the schema this build writes.
+pub fn run_migrate(root: &Path, args: MigrateArgs) -> Result<(), StoreError> {
+    let store = FurStore::open_unmigrated(root)?;
+    let _lock = if args.dry_run { None } else { Some(store.lock()?) };
+
+    let report = store.migrate(args.dry_run)?;
+    if report.is_noop() {
+        println!("✔️ Schema is up to date ({}).", report.to);
+        return Ok(());
+    }
+
+    println!(
+        "{} {} → {}",
+        "🔧 Schema".bright_cyan().bold(),
+        report.from.bright_yellow(),
+        report.to.bright_green().bold()
+    );
+    for step in &report.steps {
+        println!("  • {} → {}: {}", step.from, step.to, step.description);
+    }
+    for path in &report.changed {
+        println!("    {}", path.display().to_string().bright_black());
+    }
+
+    if report.dry_run {
+        println!("{} file(s) would change (dry run, nothing written).", report.changed.len());
+    } else {
+        println!("✔️ Migrated {} file(s).", report.changed.len());
+    }
+    Ok(())
+}
@@ -10,3 +10,16 @@ pub mod jump;
 pub mod tree;
 pub mod save;
 pub mod run;
+pub mod migrate;
+pub mod doctor;
+pub mod gc;
+pub mod edit;
+pub mod show;
+pub mod rm;
+pub mod restore;
+pub mod mv;
+pub mod merge;
+pub mod mark;
+pub mod reflog;
+pub mod undo;
+pub mod branch;
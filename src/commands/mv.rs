@@ -0,0 +1,58 @@
+use clap::Parser;
+use colored::*;
+
+use crate::store::{short_id, FurStore, StoreError};
+
+/// Arguments for the `mv` subcommand
+#[derive(Parser, Debug)]
+#[command(group = clap::ArgGroup::new("dest").required(true).multiple(true).args(["parent", "thread"]))]
+pub struct MvArgs {
+    /// Message to move (a unique prefix is enough); its replies move with it
+    pub id: String,
+
+    /// New parent message
+    #[arg(long)]
+    pub parent: Option<String>,
+
+    /// Destination thread (without --parent, the message becomes a root there)
+    #[arg(long)]
+    pub thread: Option<String>,
+}
+
+/// Reparent a message, or carry its subtree to another thread.
+pub fn run_mv(store: &FurStore, args: MvArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let index = store.load_index()?;
+
+    let id = store.resolve_message(&args.id)?;
+    let thread = args.thread.map(|t| store.resolve_thread(&index, &t)).transpose()?;
+    // Without --thread, the parent decides where the message goes
+    let parent = match (args.parent, thread.as_deref()) {
+        (Some(p), Some(tid)) => Some(store.resolve_message_in(&p, &store.load_thread(tid)?)?),
+        (Some(p), None) => Some(store.resolve_any_message(&p)?),
+        (None, _) => None,
+    };
+
+    store.begin_op("mv");
+    let report = store.move_message(&id, parent.as_deref(), thread.as_deref())?;
+    store.commit_op(short_id(&id))?;
+
+    let dest = match report.to.parent {
+        Some(ref p) => format!("under {}", short_id(p)),
+        None => "to the root".to_string(),
+    };
+    let across = if report.from_thread != report.to_thread {
+        format!(" of thread {}", short_id(&report.to_thread))
+    } else {
+        String::new()
+    };
+    println!(
+        "{} {} {}{} ({} message(s))",
+        "📦 Moved".bright_green().bold(),
+        short_id(&id),
+        dest,
+        across,
+        report.moved
+    );
+    Ok(())
+}
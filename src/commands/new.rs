@@ -1,34 +1,21 @@
-use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::Path;
-use uuid::Uuid;
-use chrono::Utc;
 use serde_json::json;
 use colored::*;
 
-use crate::frs::avatars::{load_avatars, save_avatars, get_random_emoji_for_name};
+use crate::frs::avatars::get_random_emoji_for_name;
+use crate::store::{short_id, FurStore, Position, StoreError, StoredThread};
 
-/// Creates a new thread with a user-provided name.
-pub fn run_new(name: String) {
-    let fur_dir = Path::new(".fur");
+/// Creates a new thread with a user-provided name, initializing the
+/// store at `root` if it doesn't exist yet.
+pub fn run_new(root: &Path, name: String) -> Result<(), StoreError> {
+    let fresh = !root.exists();
+    let store = FurStore::init(root)?;
+    let _lock = store.lock()?;
 
-    // Create .fur/ directory if it doesn't exist
-    if !fur_dir.exists() {
-        fs::create_dir_all(fur_dir.join("threads")).expect("Failed to create .fur/threads");
-        fs::create_dir_all(fur_dir.join("messages")).expect("Failed to create .fur/messages");
-
-        let index_path = fur_dir.join("index.json");
-        let initial_index = json!({
-            "threads": [],
-            "active_thread": null,
-            "created_at": Utc::now().to_rfc3339(),
-            "schema_version": "0.2"
-        });
-
-        let mut file = File::create(index_path).expect("Failed to write .fur/index.json");
-        file.write_all(initial_index.to_string().as_bytes()).unwrap();
-
-        println!("{}", "[INIT] .fur/ directory created".bright_green().bold());
+    // Onboard avatars the first time .fur/ is created
+    if fresh {
+        println!("{}", format!("[INIT] {} directory created", root.display()).bright_green().bold());
 
         // === Interactive onboarding for avatars ===
 
@@ -40,7 +27,7 @@ pub fn run_new(name: String) {
             Whenever you jot without specifying an avatar, it will be attributed here."
                 .bright_cyan()
         );
-        print!("{}", "Main avatar name [me]: ");
+        print!("Main avatar name [me]: ");
         io::stdout().flush().unwrap();
         let mut main_in = String::new();
         io::stdin().read_line(&mut main_in).unwrap();
@@ -70,7 +57,7 @@ pub fn run_new(name: String) {
              Let's log at least one other avatar. This could be an AI, your boss, your therapist, or karen_from_hr."
                 .bright_cyan()
         );
-        print!("{}", "Another avatar [ai]: ");
+        print!("Another avatar [ai]: ");
         io::stdout().flush().unwrap();
         let mut other_in = String::new();
         io::stdin().read_line(&mut other_in).unwrap();
@@ -84,7 +71,7 @@ pub fn run_new(name: String) {
         );
 
         // Load current avatars (probably empty at this point)
-        let mut avatars = load_avatars();
+        let mut avatars = store.load_avatars()?;
 
         // Set main avatar pointer + emoji
         avatars["main"] = json!(main_name);
@@ -94,7 +81,7 @@ pub fn run_new(name: String) {
         let e = get_random_emoji_for_name(other_name);
         avatars[other_name] = json!(e);
 
-        save_avatars(&avatars);
+        store.save_avatars(&avatars)?;
 
         println!(
             "\n{}",
@@ -104,44 +91,22 @@ pub fn run_new(name: String) {
     }
 
     // === Create new thread ===
-    let thread_id = Uuid::new_v4().to_string();
-    let now = Utc::now().to_rfc3339();
-
-    let thread_metadata = json!({
-        "id": thread_id,
-        "created_at": now,
-        "messages": [],
-        "tags": [],
-        "title": name,
-    });
-
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-    let mut thread_file = File::create(thread_path).expect("Could not create thread file");
-    thread_file
-        .write_all(thread_metadata.to_string().as_bytes())
-        .expect("Could not write thread file");
+    let thread = StoredThread::new(&name);
+    store.save_thread(&thread)?;
 
     // Update index
-    let index_path = fur_dir.join("index.json");
-    let mut index_data: serde_json::Value =
-        serde_json::from_str(&fs::read_to_string(&index_path).unwrap()).unwrap();
-
-    index_data["threads"]
-        .as_array_mut()
-        .unwrap()
-        .push(thread_id.clone().into());
-    index_data["active_thread"] = thread_id.clone().into();
-    index_data["current_message"] = serde_json::Value::Null;
-
-    let mut index_file = File::create(index_path).unwrap();
-    index_file
-        .write_all(index_data.to_string().as_bytes())
-        .unwrap();
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+    index.threads.push(thread.id.clone());
+    store.switch_thread(&mut index, &thread.id)?;
+    store.save_index(&index)?;
+    store.append_reflog("new", before, Position::of(&index), name.as_str())?;
 
     println!(
         "{}",
-        format!("[NEW] Thread created: {} — \"{}\"", &thread_id[..8], name)
+        format!("[NEW] Thread created: {} — \"{}\"", short_id(&thread.id), name)
             .bright_green()
             .bold()
     );
+    Ok(())
 }
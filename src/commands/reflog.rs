@@ -0,0 +1,55 @@
+use clap::Parser;
+use colored::*;
+
+use crate::renderer::list::render_list;
+use crate::renderer::utils::format_timestamp;
+use crate::store::{short_id, FurStore, Position, StoreError};
+
+/// Arguments for the `reflog` subcommand
+#[derive(Parser, Debug)]
+pub struct ReflogArgs {
+    /// Show at most N entries
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+/// `fur reflog`: recent cursor moves and changes, newest first.
+pub fn run_reflog(store: &FurStore, args: ReflogArgs) -> Result<(), StoreError> {
+    let entries = store.load_reflog()?;
+    if entries.is_empty() {
+        println!("(reflog is empty)");
+        return Ok(());
+    }
+
+    let rows = entries
+        .iter()
+        .take(args.limit)
+        .enumerate()
+        .map(|(n, e)| {
+            let (date, time) = format_timestamp(&e.timestamp);
+            vec![
+                n.to_string(),
+                format!("{} {}", date, time),
+                e.action.clone(),
+                format!("{} → {}", position(&e.before, &e.after), position(&e.after, &e.before)),
+                e.detail.clone(),
+            ]
+        })
+        .collect();
+
+    render_list("Reflog", &["#", "When", "Action", "Move", "Detail"], rows, Some(0));
+    if entries.len() > args.limit {
+        println!("{}", format!("({} older, use -n to show more)", entries.len() - args.limit).bright_black());
+    }
+    println!("{}", "Return to an entry with `fur jump --reflog <#>`".bright_black());
+    Ok(())
+}
+
+/// Short message id, prefixed with the thread when it differs from `other`'s.
+fn position(pos: &Position, other: &Position) -> String {
+    let msg = pos.message.as_deref().map(short_id).unwrap_or("-");
+    match pos.thread {
+        Some(ref t) if pos.thread != other.thread => format!("{}:{}", short_id(t), msg),
+        _ => msg.to_string(),
+    }
+}
@@ -0,0 +1,49 @@
+use clap::Parser;
+use colored::*;
+
+use crate::renderer::utils::format_timestamp;
+use crate::store::{short_id, FurStore, StoreError};
+
+/// Arguments for the `restore` subcommand
+#[derive(Parser, Debug)]
+pub struct RestoreArgs {
+    /// Trashed message id (a unique prefix is enough). Omit to list the trash.
+    pub id: Option<String>,
+}
+
+/// Bring a message removed by `fur rm` back, or list what can be restored.
+pub fn run_restore(store: &FurStore, args: RestoreArgs) -> Result<(), StoreError> {
+    let Some(id) = args.id else {
+        let entries = store.list_trash()?;
+        if entries.is_empty() {
+            println!("(trash is empty)");
+        }
+        for entry in entries {
+            let (d, t) = format_timestamp(&entry.deleted_at);
+            println!(
+                "{} {} {} {}",
+                short_id(&entry.id).bright_yellow(),
+                d.cyan(),
+                t.bright_cyan(),
+                format!("{} message(s), thread {}", entry.messages.len(), short_id(&entry.thread))
+                    .bright_black()
+            );
+        }
+        return Ok(());
+    };
+
+    let _lock = store.lock()?;
+    let entry = store.restore(&id)?;
+    println!(
+        "{}",
+        format!(
+            "♻️ Restored {} ({} message(s)) into thread {}",
+            short_id(&entry.id),
+            entry.messages.len(),
+            short_id(&entry.thread)
+        )
+        .bright_green()
+        .bold()
+    );
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use clap::Parser;
+use colored::*;
+
+use crate::store::{short_id, FurStore, Position, StoreError};
+
+/// Arguments for the `rm` subcommand
+#[derive(Parser, Debug)]
+pub struct RmArgs {
+    /// Message id, unique prefix, HEAD, HEAD~N or root
+    pub id: String,
+
+    /// Also remove every reply below the message
+    #[arg(short, long)]
+    pub recursive: bool,
+}
+
+/// Move a message (or subtree) to `.fur/trash/`.
+pub fn run_rm(store: &FurStore, args: RmArgs) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
+    let id = store.resolve_message(&args.id)?;
+    let preview = store.load_message(&id)?.preview();
+
+    let before = Position::of(&store.load_index()?);
+    store.begin_op("rm");
+    let entry = store.remove_message(&id, args.recursive)?;
+    store.commit_op(preview.as_str())?;
+    let after = Position::of(&store.load_index()?);
+    store.append_reflog("rm", before, after, format!("{} ({} message(s))", preview, entry.messages.len()))?;
+
+    println!(
+        "🗑️ Removed {} {} ({} message(s) moved to trash)",
+        short_id(&id).bright_black(),
+        preview,
+        entry.messages.len()
+    );
+    println!(
+        "{}",
+        format!("   Undo with `fur restore {}`", short_id(&id)).bright_black()
+    );
+    Ok(())
+}
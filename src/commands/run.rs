@@ -1,112 +1,140 @@
-use std::fs;
 use colored::*;
-use crate::frs::{parser, persist_frs};
+use crate::frs::{parser, persist_frs, update_frs};
 use crate::commands::{timeline, tree};
 use crate::commands::timeline::TimelineArgs;
 use crate::commands::tree::TreeArgs;
+use crate::frs::ast::{Command, ScriptItem, Thread};
+use crate::frs::persist::{cleanup_ephemeral, persist_ephemeral};
+use crate::store::{short_id, FurStore, StoreError};
 
 /// Run an .frs script:
 /// - Parse into Thread (in-memory)
 /// - Execute inline commands (tree, timeline, status)
 /// - Persist once at first `store`
 /// - Ignore later `store`s
-pub fn run_frs(path: &str) {
-    let raw = fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("❌ Could not read .frs file: {}", path));
+pub fn run_frs(store: &FurStore, path: &str) -> Result<(), StoreError> {
+    run_script(store, path, false)
+}
 
-    let lines: Vec<String> = raw
-        .lines()
-        .map(|l| l.trim().to_string())
-        .filter(|l| !l.is_empty() && !l.starts_with('#'))
-        .collect();
+/// Like [`run_frs`], but `store` updates the thread with the same title in
+/// place (see [`update_frs`]) instead of replacing it.
+pub fn run_frs_update(store: &FurStore, path: &str) -> Result<(), StoreError> {
+    run_script(store, path, true)
+}
 
-    let thread = parser::parse_frs(path);
-    let mut stored = false;
+fn run_script(store: &FurStore, path: &str, update: bool) -> Result<(), StoreError> {
+    let _lock = store.lock()?;
 
-    for (lineno, line) in lines.iter().enumerate() {
-        // --- Commit point
-        if line == "store" {
-            if !stored {
-                let tid = persist_frs(&thread);
-                println!("✔️ Thread persisted at line {} → {}", lineno + 1, &tid[..8]);
-                stored = true;
-            } else {
-                eprintln!(
-                    "{}",
-                    format!("⚠️ Ignoring extra `store` at line {} — already persisted", lineno + 1)
-                        .yellow()
-                        .bold()
-                );
+    let thread = match parser::parse_frs(path, &store.load_avatars()?) {
+        Ok(thread) => thread,
+        Err(diagnostics) => {
+            for d in &diagnostics {
+                eprintln!("{}", d.render(path));
             }
-            continue;
+            let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+            return Err(StoreError::Invalid(format!(
+                "Could not run {}: {} error{}",
+                path,
+                errors,
+                if errors == 1 { "" } else { "s" }
+            )));
         }
+    };
+    for w in &thread.warnings {
+        eprintln!("{}", w.render(path));
+    }
 
-        // --- Status
-        if line.starts_with("status") {
-            with_ephemeral(stored, &thread, |tid_override| {
-                let args = crate::commands::status::StatusArgs {
-                    thread_override: tid_override,
-                };
-                crate::commands::status::run_status(args);
-            });
-            continue;
-        }
+    let commands: Vec<&Command> = thread
+        .items
+        .iter()
+        .filter_map(|item| if let ScriptItem::Command(c) = item { Some(c) } else { None })
+        .collect();
+    let mut stored = false;
 
-        // --- Timeline
-        if line.starts_with("timeline") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            let mut args = TimelineArgs {
-                verbose: false,
-                contents: false,
-                out: None,
-                thread_override: None,
-            };
-            for (i, p) in parts.iter().enumerate() {
-                if *p == "--out" {
-                    args.out = parts.get(i + 1).map(|s| s.to_string());
+    for cmd in commands {
+        match cmd.name.as_str() {
+            // --- Commit point
+            "store" => {
+                if !stored {
+                    let tid = if update { update_frs(store, &thread)? } else { persist_frs(store, &thread)? };
+                    println!("✔️ Thread persisted at line {} → {}", cmd.line_number, short_id(&tid));
+                    stored = true;
+                } else {
+                    eprintln!(
+                        "{}",
+                        format!("⚠️ Ignoring extra `store` at line {} — already persisted", cmd.line_number)
+                            .yellow()
+                            .bold()
+                    );
                 }
-                if *p == "--contents" {
-                    args.contents = true;
+            }
+
+            // --- Status
+            "status" => {
+                with_ephemeral(store, stored, &thread, |tid_override| {
+                    let args = crate::commands::status::StatusArgs {
+                        thread_override: tid_override,
+                    };
+                    crate::commands::status::run_status(store, args)
+                })?;
+            }
+
+            // --- Timeline
+            "timeline" => {
+                let mut args = TimelineArgs {
+                    verbose: false,
+                    contents: false,
+                    out: None,
+                    thread_override: None,
+                    marks: Default::default(),
+                };
+                for (i, p) in cmd.args.iter().enumerate() {
+                    if p == "--out" {
+                        args.out = cmd.args.get(i + 1).cloned();
+                    }
+                    if p == "--contents" {
+                        args.contents = true;
+                    }
                 }
+
+                with_ephemeral(store, stored, &thread, |tid_override| {
+                    let mut args = args.clone();
+                    args.thread_override = tid_override;
+                    timeline::run_timeline(store, args)
+                })?;
             }
 
-            with_ephemeral(stored, &thread, |tid_override| {
-                let mut args = args.clone();
-                args.thread_override = tid_override;
-                timeline::run_timeline(args);
-            });
-            continue;
-        }
+            // --- Tree
+            "tree" => {
+                let args = TreeArgs { thread_override: None };
+                with_ephemeral(store, stored, &thread, |tid_override| {
+                    let mut args = args.clone();
+                    args.thread_override = tid_override;
+                    tree::run_tree(store, args)
+                })?;
+            }
 
-        // --- Tree
-        if line.starts_with("tree") {
-            let args = TreeArgs { thread_override: None };
-            with_ephemeral(stored, &thread, |tid_override| {
-                let mut args = args.clone();
-                args.thread_override = tid_override;
-                tree::run_tree(args);
-            });
-            continue;
+            _ => {}
         }
-
-        // Default: skip (jots already parsed by parser::parse_frs)
     }
 
     if !stored {
         eprintln!("{}", "⚠️ Script finished without a `store` — nothing persisted.".yellow());
     }
+    Ok(())
 }
 
 /// Run a command either with an ephemeral thread (if not stored) or directly.
-fn with_ephemeral<F>(stored: bool, thread: &crate::frs::ast::Thread, mut f: F)
+fn with_ephemeral<F>(store: &FurStore, stored: bool, thread: &Thread, mut f: F) -> Result<(), StoreError>
 where
-    F: FnMut(Option<String>),
+    F: FnMut(Option<String>) -> Result<(), StoreError>,
 {
     if !stored {
-        let tid = crate::frs::persist::persist_ephemeral(thread);
-        f(Some(tid.clone()));
-        crate::frs::persist::cleanup_ephemeral(&tid);
+        let tid = persist_ephemeral(store, thread)?;
+        let result = f(Some(tid.clone()));
+        cleanup_ephemeral(store, &tid)?;
+        result
     } else {
-        f(None);
+        f(None)
     }
 }
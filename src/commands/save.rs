@@ -1,7 +1,9 @@
 use clap::Parser;
 use std::fs;
-use std::path::Path;
-use serde_json::Value;
+use std::collections::BTreeSet;
+use crate::frs::parser::parse_timestamp;
+use crate::store::navigate::Tree;
+use crate::store::{FurStore, StoreError, StoredMessage};
 
 /// Arguments for the `save` subcommand
 #[derive(Parser)]
@@ -12,100 +14,143 @@ pub struct SaveArgs {
 }
 
 /// Save the active thread back into a .frs file
-pub fn run_save(args: SaveArgs) {
-    let fur_dir = Path::new(".fur");
-    let index_path = fur_dir.join("index.json");
+pub fn run_save(store: &FurStore, args: SaveArgs) -> Result<(), StoreError> {
+    let index = store.load_index()?;
+    let thread = store.active_thread(&index)?;
 
-    if !index_path.exists() {
-        eprintln!("🚨 .fur/ not found. Run `fur new` first.");
-        return;
-    }
-
-    let index: Value =
-        serde_json::from_str(&fs::read_to_string(&index_path).expect("❌ Cannot read index.json"))
-            .unwrap();
-
-    let thread_id = match index["active_thread"].as_str() {
-        Some(id) => id,
-        None => {
-            eprintln!("⚠️ No active thread.");
-            return;
-        }
-    };
-
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-    let thread: Value =
-        serde_json::from_str(&fs::read_to_string(&thread_path).expect("❌ Cannot read thread"))
-            .unwrap();
-
-    let title = thread["title"].as_str().unwrap_or("Untitled");
-    let safe_title = title.replace(" ", "_");
+    let title = &thread.title;
+    let safe_title = title.replace(' ', "_");
 
     let output_path = args
         .out
         .unwrap_or_else(|| format!("{}.frs", safe_title));
 
+    let avatars = store.load_avatars()?;
+    let tree = store.load_tree(&thread);
+    let used: BTreeSet<&str> = tree.values().map(avatar_of).collect();
+
+    // The script's `user`: main when it appears, else whoever spoke first
+    let main = avatars.get("main").and_then(|v| v.as_str());
+    let first = thread.messages.first().and_then(|id| tree.get(id)).map(avatar_of);
+    let user = main.filter(|m| used.contains(m)).or(first).or(main);
+
     let mut out = String::new();
 
     // ---- header
-    out.push_str(&format!("new \"{}\"\n", title));
-    if let Some(tags) = thread["tags"].as_array() {
-        if !tags.is_empty() {
-            let tags_str = tags
-                .iter()
-                .filter_map(|t| t.as_str())
-                .map(|t| format!("\"{}\"", t))
-                .collect::<Vec<_>>()
-                .join(", ");
-            out.push_str(&format!("tags = [{}]\n\n", tags_str));
+    out.push_str(&format!("new {}\n", quote(title)));
+    if !thread.tags.is_empty() {
+        let tags_str = thread
+            .tags
+            .iter()
+            .map(|t| quote(t))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!("tags = [{}]\n", tags_str));
+    }
+    out.push('\n');
+    if let Some(user) = user {
+        out.push_str(&format!("user = {}\n", user));
+    }
+    for name in &used {
+        if let Some(emoji) = avatars.get(*name).and_then(|v| v.as_str()).filter(|_| *name != "main") {
+            out.push_str(&format!("avatar {} = {}\n", name, quote(emoji)));
         }
     }
+    out.push('\n');
 
     // ---- messages (recursive)
-    for msg_id in thread["messages"].as_array().unwrap_or(&vec![]) {
-        if let Some(mid) = msg_id.as_str() {
-            out.push_str(&render_message(mid, 0, fur_dir));
-        }
+    for mid in &thread.messages {
+        render_message(&mut out, &tree, mid, user, 0);
     }
+    // So `fur run` imports it again
+    out.push_str("\nstore\n");
 
-    fs::write(&output_path, out).expect("❌ Could not write .frs file");
+    fs::write(&output_path, out).map_err(|e| StoreError::io(&output_path, e))?;
     println!("💾 Saved thread \"{}\" to {}", title, output_path);
+    Ok(())
 }
 
-fn render_message(msg_id: &str, indent: usize, fur_dir: &Path) -> String {
-    let msg_path = fur_dir.join("messages").join(format!("{}.json", msg_id));
-    let content = match fs::read_to_string(&msg_path) {
-        Ok(c) => c,
-        Err(_) => return String::new(),
-    };
-    let msg: Value = serde_json::from_str(&content).unwrap();
+fn avatar_of(msg: &StoredMessage) -> &str {
+    if msg.avatar.is_empty() { "anon" } else { &msg.avatar }
+}
 
-    let mut out = String::new();
+fn render_message(out: &mut String, tree: &Tree, msg_id: &str, user: Option<&str>, indent: usize) {
+    let Some(msg) = tree.get(msg_id) else {
+        return;
+    };
     let pad = "    ".repeat(indent);
 
-    let avatar = msg["avatar"].as_str().unwrap_or("anon");
+    // Flags first: a text block has to end its line
+    out.push_str(&format!("{}jot", pad));
+    let avatar = avatar_of(msg);
+    if Some(avatar) != user {
+        out.push_str(&format!(" {}", avatar));
+    }
+    if let Some(ts) = parse_timestamp(&msg.timestamp) {
+        out.push_str(&format!(" @{}", ts));
+    }
+    out.push_str(&format!(" id={}", msg.id));
+    for tag in &msg.tags {
+        out.push_str(&format!(" #{}", tag));
+    }
+    if let Some(ref file) = msg.markdown {
+        out.push_str(&format!(" --file {}", quote(file)));
+    }
+    if let Some(ref att) = msg.attachment {
+        out.push_str(&format!(" --attach {}", quote(att)));
+    }
+    match msg.text {
+        Some(ref text) => out.push_str(&format!(" {}\n", text_literal(text, &pad))),
+        None if msg.markdown.is_none() && msg.attachment.is_none() => out.push_str(" \"\"\n"),
+        None => out.push('\n'),
+    }
 
-    if let Some(text) = msg["text"].as_str() {
-        out.push_str(&format!("{}jot {} \"{}\"\n", pad, avatar, text));
-    } else if let Some(file) = msg["markdown"].as_str() {
-        out.push_str(&format!("{}jot {} --file \"{}\"\n", pad, avatar, file));
-    } else if let Some(att) = msg["attachment"].as_str() {
-        out.push_str(&format!("{}jot {} --img \"{}\"\n", pad, avatar, att));
+    for block in &msg.branches {
+        out.push_str(&format!("{}branch {{\n", pad));
+        for cid in block {
+            render_message(out, tree, cid, user, indent + 1);
+        }
+        out.push_str(&format!("{}}}\n", pad));
     }
 
-    if let Some(branches) = msg["branches"].as_array() {
-        for block in branches {
-            if let Some(arr) = block.as_array() {
-                out.push_str(&format!("{}branch {{\n", pad));
-                for child in arr {
-                    if let Some(cid) = child.as_str() {
-                        out.push_str(&render_message(cid, indent + 1, fur_dir));
-                    }
-                }
-                out.push_str(&format!("{}}}\n", pad));
-            }
+    // Children outside every branch group
+    let replies: Vec<&String> = msg.children.iter().filter(|c| !msg.branches.iter().flatten().any(|b| b == *c)).collect();
+    if !replies.is_empty() {
+        out.push_str(&format!("{}reply {{\n", pad));
+        for cid in replies {
+            render_message(out, tree, cid, user, indent + 1);
         }
+        out.push_str(&format!("{}}}\n", pad));
     }
+}
 
+/// A `"quoted"` string with `\\`, `"`, newlines and tabs escaped.
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
     out
 }
+
+/// Multi-line text as a `<<<` block indented one level below `pad`;
+/// anything a block can't hold verbatim falls back to [`quote`].
+fn text_literal(text: &str, pad: &str) -> String {
+    if !text.contains('\n') || text.contains('\r') || text.split('\n').any(|l| l.trim() == ">>>") {
+        return quote(text);
+    }
+    let inner = format!("{}    ", pad);
+    let body = text
+        .split('\n')
+        .map(|l| if l.is_empty() { String::new() } else { format!("{}{}", inner, l) })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("<<<\n{}\n{}>>>", body, inner)
+}
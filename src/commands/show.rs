@@ -0,0 +1,90 @@
+use clap::Parser;
+use colored::*;
+
+use crate::frs::avatars::resolve_avatar;
+use crate::renderer::utils::format_timestamp;
+use crate::store::{FurStore, StoreError};
+
+/// Arguments for the `show` subcommand
+#[derive(Parser, Debug)]
+pub struct ShowArgs {
+    /// Message id, unique prefix, HEAD, HEAD~N or root
+    pub id: String,
+
+    /// List every version of the message, oldest first
+    #[arg(long)]
+    pub revisions: bool,
+}
+
+/// Print a single message, optionally with its edit history.
+pub fn run_show(store: &FurStore, args: ShowArgs) -> Result<(), StoreError> {
+    let id = store.resolve_any_message(&args.id)?;
+    let msg = store.load_message(&id)?;
+    let avatars = store.load_avatars()?;
+    let (name, emoji) = resolve_avatar(&avatars, &msg.avatar);
+    let (date, time) = format_timestamp(&msg.timestamp);
+
+    println!(
+        "{} {} [{}] {} {}",
+        date.cyan(),
+        time.bright_cyan().bold(),
+        emoji,
+        name.bright_yellow(),
+        format!("({})", id).bright_black()
+    );
+    if let Some(ref md) = msg.markdown {
+        println!("{}", format!("📄 {}", md).bright_black());
+    }
+    if let Some(ref att) = msg.attachment {
+        println!("{}", format!("🖼️ {}", att).bright_black());
+    }
+    if !msg.tags.is_empty() {
+        let tags = msg.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ");
+        println!("{}", tags.bright_black());
+    }
+    println!("{}", store.message_content(&msg).unwrap_or_else(|e| e.to_string()));
+
+    if msg.revisions.is_empty() {
+        return Ok(());
+    }
+
+    if !args.revisions {
+        let (d, t) = format_timestamp(&msg.revisions[msg.revisions.len() - 1].timestamp);
+        println!(
+            "{}",
+            format!("✏️ edited {}× (last {} {}) — see --revisions", msg.revisions.len(), d, t).bright_black()
+        );
+        return Ok(());
+    }
+
+    // Version k was written when version k-1 was replaced
+    println!("{}", "─────────────────────────────".bright_black());
+    let mut written_at = msg.timestamp.clone();
+    for (i, rev) in msg.revisions.iter().enumerate() {
+        let content = rev
+            .markdown_contents
+            .as_deref()
+            .or(rev.text.as_deref())
+            .unwrap_or("<no content>");
+        print_version(i + 1, &written_at, i == 0, false, content);
+        written_at = rev.timestamp.clone();
+    }
+    let current = store.message_content(&msg)?;
+    print_version(msg.revisions.len() + 1, &written_at, false, true, &current);
+    Ok(())
+}
+
+fn print_version(n: usize, ts: &str, original: bool, current: bool, content: &str) {
+    let (d, t) = format_timestamp(ts);
+    let tag = if current {
+        " (current)".bright_green().bold().to_string()
+    } else if original {
+        " (original)".bright_black().to_string()
+    } else {
+        String::new()
+    };
+    println!("{} {} {}{}", format!("v{}", n).bright_magenta().bold(), d.cyan(), t.bright_cyan(), tag);
+    for line in content.lines() {
+        println!("    {}", line);
+    }
+}
@@ -1,112 +1,243 @@
-use std::fs;
-use std::path::Path;
-use serde_json::{Value, json};
-use clap::Parser;
+use std::io::{self, Write};
+use clap::{Parser, Subcommand};
+use colored::*;
 use crate::renderer::list::render_list;
+use crate::store::{short_id, FurStore, Index, Position, StoreError};
 
 /// Arguments for the `thread` command
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct ThreadArgs {
+    #[command(subcommand)]
+    pub action: Option<ThreadAction>,
+
     /// Thread ID or prefix to switch
     pub id: Option<String>,
 
     /// View all threads
     #[arg(long)]
     pub view: bool,
+
+    /// Include archived threads in the list
+    #[arg(long)]
+    pub all: bool,
 }
 
-/// Main entry point for the `thread` command
-pub fn run_thread(args: ThreadArgs) {
-    let fur_dir = Path::new(".fur");
-    let index_path = fur_dir.join("index.json");
+#[derive(Subcommand)]
+pub enum ThreadAction {
+    /// Change a thread's title
+    Rename {
+        /// New title
+        title: String,
+
+        /// Thread ID or prefix (defaults to the active thread)
+        #[arg(short, long)]
+        thread: Option<String>,
+    },
+
+    /// Delete a thread and the messages only it uses
+    Rm {
+        /// Thread ID or prefix
+        thread: String,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Hide a thread from the default `fur thread` list
+    Archive {
+        /// Thread ID or prefix (defaults to the active thread)
+        #[arg(short, long)]
+        thread: Option<String>,
+    },
+
+    /// Show an archived thread in the default list again
+    Unarchive {
+        /// Thread ID or prefix (defaults to the active thread)
+        #[arg(short, long)]
+        thread: Option<String>,
+    },
+
+    /// Add or remove tags
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TagAction {
+    /// Add one or more tags
+    Add {
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Thread ID or prefix (defaults to the active thread)
+        #[arg(short, long)]
+        thread: Option<String>,
+    },
+
+    /// Remove one or more tags
+    Rm {
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Thread ID or prefix (defaults to the active thread)
+        #[arg(short, long)]
+        thread: Option<String>,
+    },
+}
 
-    if !index_path.exists() {
-        eprintln!("🚨 .fur/ not found. Run `fur new` first.");
-        return;
+/// Main entry point for the `thread` command
+pub fn run_thread(store: &FurStore, args: ThreadArgs) -> Result<(), StoreError> {
+    if let Some(action) = args.action {
+        let _lock = store.lock()?;
+        return run_action(store, action);
     }
 
-    let mut index: Value =
-        serde_json::from_str(&fs::read_to_string(&index_path).unwrap()).unwrap();
+    let switching = args.id.is_some() && !args.view;
+    let _lock = if switching { Some(store.lock()?) } else { None };
+    let mut index = store.load_index()?;
 
     // ------------------------
     // VIEW ALL THREADS
     // ------------------------
-    if args.view || args.id.is_none() {
-        let empty_vec: Vec<Value> = Vec::new();
-        let threads = index["threads"].as_array().unwrap_or(&empty_vec);
-        let active = index["active_thread"].as_str().unwrap_or("");
+    if !switching {
+        let active = index.active_thread.as_deref().unwrap_or("");
 
         let mut rows = Vec::new();
         let mut active_idx = None;
+        let mut hidden = 0;
 
-        for (i, tid) in threads.iter().enumerate() {
-            if let Some(tid_str) = tid.as_str() {
-                let thread_path = fur_dir.join("threads").join(format!("{}.json", tid_str));
-                if let Ok(content) = fs::read_to_string(thread_path) {
-                    if let Ok(thread_json) = serde_json::from_str::<Value>(&content) {
-                        let title = thread_json["title"].as_str().unwrap_or("Untitled");
-                        let short_id = &tid_str[..8];
-                        rows.push(vec![short_id.to_string(), title.to_string()]);
-                        if tid_str == active {
-                            active_idx = Some(i);
-                        }
-                    }
-                }
+        for thread in store.load_threads(&index) {
+            if thread.archived && !args.all {
+                hidden += 1;
+                continue;
+            }
+            let mut title = thread.title.clone();
+            if thread.archived {
+                title.push_str(" [archived]");
+            }
+            let tags = thread.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ");
+            rows.push(vec![short_id(&thread.id).to_string(), title, tags]);
+            if thread.id == active {
+                active_idx = Some(rows.len() - 1);
             }
         }
 
-        render_list("Threads", &["ID", "Title"], rows, active_idx);
-        return;
+        render_list("Threads", &["ID", "Title", "Tags"], rows, active_idx);
+        if hidden > 0 {
+            println!("{}", format!("({} archived, use --all to show)", hidden).bright_black());
+        }
+        return Ok(());
     }
 
     // ------------------------
     // SWITCH ACTIVE THREAD
     // ------------------------
     if let Some(tid) = args.id {
-        let empty_vec: Vec<Value> = Vec::new();
-        let threads: Vec<String> = index["threads"]
-            .as_array()
-            .unwrap_or(&empty_vec)
-            .iter()
-            .filter_map(|t| t.as_str().map(|s| s.to_string()))
-            .collect();
-
-        // Try exact match first
-        let mut found = threads.iter().find(|&s| s == &tid);
-
-        // If no exact match, try prefix match
-        if found.is_none() {
-            let matches: Vec<&String> = threads
-                .iter()
-                .filter(|s| s.starts_with(&tid))
-                .collect();
-
-            if matches.len() == 1 {
-                found = Some(matches[0]);
-            } else if matches.len() > 1 {
-                eprintln!("❌ Ambiguous prefix '{}'. Matches: {:?}", tid, matches);
-                return;
-            }
+        let tid_full = store.resolve_thread(&index, &tid)?;
+        let thread = store.load_thread(&tid_full)?;
+
+        let before = Position::of(&index);
+        store.switch_thread(&mut index, &tid_full)?;
+        store.save_index(&index)?;
+        store.append_reflog("thread", before, Position::of(&index), thread.title.as_str())?;
+
+        println!("✔️ Switched active thread to {} \"{}\"", short_id(&tid_full), thread.title);
+        if let Some(ref cursor) = index.current_message {
+            println!("{}", format!("   resumed at {}", short_id(cursor)).bright_black());
         }
+    }
+    Ok(())
+}
 
-        let tid_full = match found {
-            Some(s) => s,
-            None => {
-                eprintln!("❌ Thread not found: {}", tid);
-                return;
-            }
-        };
+/// `-t <thread>` if given, else the active thread.
+fn target_thread(store: &FurStore, index: &Index, thread: Option<String>) -> Result<String, StoreError> {
+    match thread {
+        Some(prefix) => store.resolve_thread(index, &prefix),
+        None => index.active_thread.clone().ok_or(StoreError::NoActiveThread),
+    }
+}
 
-        // ✅ Now we can safely mutate index
-        index["active_thread"] = json!(tid_full);
-        index["current_message"] = serde_json::Value::Null;
-        fs::write(&index_path, serde_json::to_string_pretty(&index).unwrap()).unwrap();
+fn run_action(store: &FurStore, action: ThreadAction) -> Result<(), StoreError> {
+    let index = store.load_index()?;
+
+    match action {
+        ThreadAction::Rename { title, thread } => {
+            let mut t = store.load_thread(&target_thread(store, &index, thread)?)?;
+            let old = std::mem::replace(&mut t.title, title);
+            store.begin_op("thread rename");
+            store.save_thread(&t)?;
+            store.commit_op(t.title.as_str())?;
+            println!("✔️ Renamed {} \"{}\" → \"{}\"", short_id(&t.id), old, t.title);
+        }
 
-        let thread_path = fur_dir.join("threads").join(format!("{}.json", tid_full));
-        let content = fs::read_to_string(thread_path).unwrap();
-        let thread_json: Value = serde_json::from_str(&content).unwrap();
-        let title = thread_json["title"].as_str().unwrap_or("Untitled");
+        ThreadAction::Rm { thread, yes } => {
+            let tid = store.resolve_thread(&index, &thread)?;
+            let t = store.load_thread(&tid)?;
+            if !yes {
+                print!("Delete thread \"{}\" ({}) and its messages? [y/N]: ", t.title, short_id(&tid));
+                io::stdout().flush().unwrap();
+                let mut input = String::new();
+                io::stdin().read_line(&mut input).unwrap();
+                let response = input.trim().to_lowercase();
+                if response != "y" && response != "yes" {
+                    println!("🚫 Kept thread \"{}\".", t.title);
+                    return Ok(());
+                }
+            }
+            let before = Position::of(&index);
+            store.begin_op("thread rm");
+            let deleted = store.delete_thread(&tid)?;
+            store.commit_op(t.title.as_str())?;
+            let after = Position::of(&store.load_index()?);
+            store.append_reflog("thread rm", before, after, format!("{} ({} message(s))", t.title, deleted))?;
+            println!(
+                "🗑️ Deleted thread {} \"{}\" ({} message(s))",
+                short_id(&tid),
+                t.title,
+                deleted
+            );
+        }
 
-        println!("✔️ Switched active thread to {} \"{}\"", &tid_full[..8], title);
+        ThreadAction::Archive { thread } => set_archived(store, &index, thread, true)?,
+
+        ThreadAction::Unarchive { thread } => set_archived(store, &index, thread, false)?,
+
+        ThreadAction::Tag { action } => {
+            let (tags, thread, add) = match action {
+                TagAction::Add { tags, thread } => (tags, thread, true),
+                TagAction::Rm { tags, thread } => (tags, thread, false),
+            };
+            let mut t = store.load_thread(&target_thread(store, &index, thread)?)?;
+            for tag in tags {
+                let tag = tag.trim_start_matches('#').to_string();
+                if add && !t.tags.contains(&tag) {
+                    t.tags.push(tag);
+                } else if !add {
+                    t.tags.retain(|x| x != &tag);
+                }
+            }
+            store.begin_op(if add { "thread tag add" } else { "thread tag rm" });
+            store.save_thread(&t)?;
+            store.commit_op(t.title.as_str())?;
+            let shown = t.tags.iter().map(|x| format!("#{}", x)).collect::<Vec<_>>().join(" ");
+            let shown = if shown.is_empty() { "(no tags)".to_string() } else { shown };
+            println!("🏷️ {} \"{}\": {}", short_id(&t.id), t.title, shown);
+        }
     }
+    Ok(())
+}
+
+fn set_archived(store: &FurStore, index: &Index, thread: Option<String>, archive: bool) -> Result<(), StoreError> {
+    let mut t = store.load_thread(&target_thread(store, index, thread)?)?;
+    t.archived = archive;
+    store.begin_op(if archive { "thread archive" } else { "thread unarchive" });
+    store.save_thread(&t)?;
+    store.commit_op(t.title.as_str())?;
+    let verb = if archive { "📦 Archived" } else { "📤 Unarchived" };
+    println!("{} {} \"{}\"", verb, short_id(&t.id), t.title);
+    Ok(())
 }
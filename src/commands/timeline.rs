@@ -1,6 +1,5 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use serde_json::{Value, json};
 use clap::Parser;
 
 use crate::renderer::{
@@ -8,6 +7,7 @@ use crate::renderer::{
     markdown::render_message_md,
     pdf::export_to_pdf,
 };
+use crate::store::{FurStore, StoreError};
 
 
 /// Args for timeline command
@@ -22,46 +22,36 @@ pub struct TimelineArgs {
 
     #[clap(skip)]
     pub thread_override: Option<String>,
+
+    /// Bookmark names by message id, filled in by `run_timeline`
+    #[clap(skip)]
+    pub marks: HashMap<String, Vec<String>>,
 }
 
 
 /// Main entry for timeline
-pub fn run_timeline(args: TimelineArgs) {
-    let fur_dir = Path::new(".fur");
-    let index_path = fur_dir.join("index.json");
-    if !index_path.exists() {
-        eprintln!("🚨 .fur/ not found. Run `fur new` first.");
-        return;
-    }
+pub fn run_timeline(store: &FurStore, mut args: TimelineArgs) -> Result<(), StoreError> {
 
     // Load thread metadata
-    let index: Value = serde_json::from_str(&fs::read_to_string(&index_path).unwrap()).unwrap();
-    let thread_id = if let Some(ref override_id) = args.thread_override {
-        override_id
-    } else {
-        index["active_thread"].as_str().unwrap_or("")
+    let index = store.load_index()?;
+    let thread = match args.thread_override {
+        Some(ref override_id) => store.load_thread(override_id)?,
+        None => store.active_thread(&index)?,
     };
-
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-    let thread_json: Value = serde_json::from_str(&fs::read_to_string(&thread_path).unwrap()).unwrap();
-
-    let thread_title = thread_json["title"].as_str().unwrap_or("Untitled");
+    let thread_title = &thread.title;
+    args.marks = store.marks_by_message(&index, &thread);
 
     // Load avatars
-    let avatars: Value = serde_json::from_str(
-        &fs::read_to_string(fur_dir.join("avatars.json"))
-            .unwrap_or_else(|_| "{}".to_string())
-    ).unwrap_or(json!({}));
+    let avatars = store.load_avatars()?;
 
     // Root messages (ids only)
-    let empty_vec: Vec<Value> = Vec::new();
-    let root_msgs = thread_json["messages"].as_array().unwrap_or(&empty_vec);
+    let root_msgs = &thread.messages;
 
     // --- PDF mode
     if let Some(path) = &args.out {
         if path.ends_with(".pdf") {
-            export_to_pdf(&fur_dir, thread_title, root_msgs, &args, &avatars, path);
-            return;
+            export_to_pdf(store, thread_title, root_msgs, &args, &avatars, path);
+            return Ok(());
         }
 
 
@@ -70,21 +60,18 @@ pub fn run_timeline(args: TimelineArgs) {
         out_content.push_str(&format!("# {}\n\n", thread_title));
 
         for mid in root_msgs {
-            if let Some(mid_str) = mid.as_str() {
-                render_message_md(&fur_dir, mid_str, "Root".to_string(), &args, &avatars, &mut out_content);
-            }
+            render_message_md(store, mid, "Root".to_string(), &args, &avatars, &mut out_content);
         }
 
-        fs::write(path, out_content).expect("❌ Failed writing Markdown file");
+        fs::write(path, out_content).map_err(|e| StoreError::io(path, e))?;
         println!("✔️ Timeline exported to {}", path);
-        return;
+        return Ok(());
     }
 
     // --- Terminal mode
     println!("Thread: {}", thread_title);
     for mid in root_msgs {
-        if let Some(mid_str) = mid.as_str() {
-            render_message(&fur_dir, mid_str, "Root".to_string(), &args, &avatars);
-        }
+        render_message(store, mid, "Root".to_string(), &args, &avatars);
     }
+    Ok(())
 }
@@ -0,0 +1,78 @@
+use clap::Parser;
+use colored::*;
+
+use crate::renderer::list::render_list;
+use crate::renderer::utils::format_timestamp;
+use crate::store::{FurStore, Operation, Position, StoreError};
+
+/// Arguments for `undo` and `redo`
+#[derive(Parser, Debug)]
+pub struct UndoArgs {
+    /// Overwrite files that changed after the operation ran
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// List recorded operations instead
+    #[arg(short, long)]
+    pub list: bool,
+}
+
+/// `fur undo`: put back every file the last operation changed.
+pub fn run_undo(store: &FurStore, args: UndoArgs) -> Result<(), StoreError> {
+    if args.list {
+        return list_ops(store);
+    }
+    let _lock = store.lock()?;
+    let before = Position::of(&store.load_index()?);
+    let op = store.undo(args.force)?;
+    store.append_reflog("undo", before, Position::of(&store.load_index()?), describe(&op))?;
+    println!("↩️ Undid {} ({} file(s) restored)", describe(&op).bright_yellow(), op.files.len());
+    println!("{}", "   Redo with `fur redo`".bright_black());
+    Ok(())
+}
+
+/// `fur redo`: re-apply the operation `fur undo` last reverted.
+pub fn run_redo(store: &FurStore, args: UndoArgs) -> Result<(), StoreError> {
+    if args.list {
+        return list_ops(store);
+    }
+    let _lock = store.lock()?;
+    let before = Position::of(&store.load_index()?);
+    let op = store.redo(args.force)?;
+    store.append_reflog("redo", before, Position::of(&store.load_index()?), describe(&op))?;
+    println!("↪️ Redid {} ({} file(s) rewritten)", describe(&op).bright_yellow(), op.files.len());
+    Ok(())
+}
+
+fn describe(op: &Operation) -> String {
+    if op.detail.is_empty() {
+        op.name.clone()
+    } else {
+        format!("{} \"{}\"", op.name, op.detail)
+    }
+}
+
+fn list_ops(store: &FurStore) -> Result<(), StoreError> {
+    let ops = store.load_ops()?;
+    if ops.is_empty() {
+        println!("(nothing to undo)");
+        return Ok(());
+    }
+    let rows = ops
+        .iter()
+        .rev()
+        .map(|op| {
+            let (date, time) = format_timestamp(&op.timestamp);
+            vec![
+                op.seq.to_string(),
+                format!("{} {}", date, time),
+                describe(op),
+                op.files.len().to_string(),
+                if op.undone { "undone".to_string() } else { String::new() },
+            ]
+        })
+        .collect();
+    let next = ops.iter().rev().position(|o| !o.undone);
+    render_list("Operations", &["#", "When", "Operation", "Files", "State"], rows, next);
+    Ok(())
+}
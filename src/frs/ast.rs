@@ -1,10 +1,21 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 
+use crate::frs::diagnostic::FrsDiagnostic;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Thread {
     pub title: String,
     pub tags: Vec<String>,
     pub items: Vec<ScriptItem>,   // not only messages
+
+    /// `avatar name = "emoji"` declarations
+    #[serde(default)]
+    pub avatars: BTreeMap<String, String>,
+
+    /// Problems the parser worked around
+    #[serde(skip)]
+    pub warnings: Vec<FrsDiagnostic>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -15,6 +26,20 @@ pub struct Message {
     pub attachment: Option<String>,    // image or other binary
     pub children: Vec<Message>,
     pub branches: Vec<Vec<Message>>,
+    /// Children outside any branch group (`reply { }`)
+    #[serde(default)]
+    pub replies: Vec<Message>,
+    /// `@…` attribute, normalized to RFC 3339
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// `id=…` attribute
+    #[serde(default)]
+    pub id: Option<String>,
+    /// `#tag` attributes
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub line: usize,                   // source line of the `jot`
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -23,6 +48,16 @@ pub enum ScriptItem {
     Command(Command),   // timeline, tree, store...
 }
 
+impl ScriptItem {
+    /// Source line the item starts on.
+    pub fn line(&self) -> usize {
+        match self {
+            ScriptItem::Message(m) => m.line,
+            ScriptItem::Command(c) => c.line_number,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Command {
     pub name: String,             // "timeline"
@@ -1,25 +1,6 @@
-use serde_json::{json, Value};
-use std::fs;
-use std::path::Path;
+use serde_json::Value;
 use rand::prelude::IndexedRandom;
 
-pub fn load_avatars() -> Value {
-    let path = Path::new(".fur/avatars.json");
-    if path.exists() {
-        let content = fs::read_to_string(path).unwrap_or_default();
-        serde_json::from_str(&content).unwrap_or_else(|_| json!({}))
-    } else {
-        json!({})
-    }
-}
-
-pub fn save_avatars(avatars: &Value) {
-    let path = Path::new(".fur/avatars.json");
-    if let Ok(serialized) = serde_json::to_string_pretty(avatars) {
-        let _ = fs::write(path, serialized);
-    }
-}
-
 pub fn resolve_avatar(avatars: &Value, key: &str) -> (String, String) {
     // If key matches a known avatar name → return (name, emoji)
     if let Some(emoji) = avatars.get(key).and_then(|v| v.as_str()) {
@@ -0,0 +1,148 @@
+//! Errors and warnings found while reading a `.frs` script.
+
+use std::fmt;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+/// A location in the source: 1-based line and column, length in chars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, col: usize, len: usize) -> Self {
+        Span { line, col, len }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The script can't be run
+    Error,
+    /// Something was skipped or guessed; the script still runs
+    Warning,
+}
+
+/// One problem in a script, with the offending source line.
+#[derive(Debug, Clone)]
+pub struct FrsDiagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// `None` for problems with the file as a whole (e.g. unreadable)
+    pub span: Option<Span>,
+    /// The full source line `span` points into
+    pub snippet: String,
+    pub help: Option<String>,
+}
+
+impl FrsDiagnostic {
+    pub fn error(message: impl Into<String>, span: Span, snippet: &str) -> Self {
+        Self::new(Severity::Error, message, span, snippet)
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span, snippet: &str) -> Self {
+        Self::new(Severity::Warning, message, span, snippet)
+    }
+
+    /// An error about the file itself, with no location in it.
+    pub fn file_error(message: impl Into<String>) -> Self {
+        FrsDiagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            span: None,
+            snippet: String::new(),
+            help: None,
+        }
+    }
+
+    fn new(severity: Severity, message: impl Into<String>, span: Span, snippet: &str) -> Self {
+        FrsDiagnostic {
+            severity,
+            message: message.into(),
+            span: Some(span),
+            snippet: snippet.trim_end().to_string(),
+            help: None,
+        }
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Compiler-style report:
+    ///
+    /// ```text
+    /// warning: `branch` with no preceding jot
+    ///  --> notes.frs:4:1
+    ///   |
+    /// 4 | branch {
+    ///   | ^^^^^^
+    /// ```
+    ///
+    /// Diagnostics without a span are just the first line (and any help).
+    pub fn render(&self, path: &str) -> String {
+        let label = match self.severity {
+            Severity::Error => "error".red().bold(),
+            Severity::Warning => "warning".yellow().bold(),
+        };
+        let mut out = format!("{}: {}\n", label, self.message.bold());
+        let Some(span) = self.span else {
+            if let Some(ref help) = self.help {
+                out.push_str(&format!("{} {}\n", "=".bright_blue().bold(), format!("help: {}", help).bold()));
+            }
+            return out;
+        };
+
+        let gutter = " ".repeat(span.line.to_string().len());
+        let bar = "|".bright_blue().bold();
+        out.push_str(&format!(
+            "{}{} {}:{}:{}\n",
+            gutter,
+            "-->".bright_blue().bold(),
+            path,
+            span.line,
+            span.col
+        ));
+        out.push_str(&format!("{} {}\n", gutter, bar));
+        out.push_str(&format!(
+            "{} {} {}\n",
+            span.line.to_string().bright_blue().bold(),
+            bar,
+            self.snippet
+        ));
+        let carets = "^".repeat(span.len.max(1));
+        let carets = match self.severity {
+            Severity::Error => carets.red().bold(),
+            Severity::Warning => carets.yellow().bold(),
+        };
+        out.push_str(&format!(
+            "{} {} {}{}\n",
+            gutter,
+            bar,
+            " ".repeat(span.col.saturating_sub(1)),
+            carets
+        ));
+        if let Some(ref help) = self.help {
+            out.push_str(&format!("{} {} {}\n", gutter, "=".bright_blue().bold(), format!("help: {}", help).bold()));
+        }
+        out
+    }
+}
+
+impl fmt::Display for FrsDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_error() { "error" } else { "warning" };
+        match self.span {
+            Some(span) => write!(f, "{}:{}: {}: {}", span.line, span.col, kind, self.message),
+            None => write!(f, "{}: {}", kind, self.message),
+        }
+    }
+}
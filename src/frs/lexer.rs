@@ -0,0 +1,234 @@
+//! Turns `.frs` source into tokens with spans.
+//!
+//! The language is line-oriented: a [`TokenKind::Newline`] ends every
+//! statement. A `#` that starts a line makes it a comment.
+//!
+//! Text comes in two forms:
+//! - `"quoted"` strings, with `\"`, `\\`, `\n` and `\t` escapes. They may run
+//!   over several lines; each continuation line is trimmed so indented
+//!   text reads naturally.
+//! - raw blocks, opened by `<<<` or `"""` at the end of a line and closed
+//!   by `>>>` / `"""` on a line of its own. Everything in between is kept
+//!   verbatim (`#` lines, quotes, indentation) except the closing line's
+//!   indentation, which is removed from every line.
+
+use crate::frs::diagnostic::{FrsDiagnostic, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A bare word: keyword, avatar, flag, path…
+    Word(String),
+    /// A `"quoted"` string, without its quotes
+    Str(String),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Comma,
+    Equals,
+    Newline,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl TokenKind {
+    /// How the token reads in a message.
+    pub fn describe(&self) -> String {
+        match self {
+            TokenKind::Word(w) => format!("`{}`", w),
+            TokenKind::Str(_) => "a quoted string".to_string(),
+            TokenKind::LBrace => "`{`".to_string(),
+            TokenKind::RBrace => "`}`".to_string(),
+            TokenKind::LBracket => "`[`".to_string(),
+            TokenKind::RBracket => "`]`".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            TokenKind::Equals => "`=`".to_string(),
+            TokenKind::Newline => "end of line".to_string(),
+        }
+    }
+}
+
+/// Tokenize `source`. Problems that can be worked around (an unterminated
+/// string) come back as warnings next to the tokens.
+pub fn lex(source: &str) -> (Vec<Token>, Vec<FrsDiagnostic>) {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut tokens = Vec::new();
+    let mut warnings = Vec::new();
+    let mut ln = 0;
+
+    while ln < lines.len() {
+        // Whole-line comment
+        if lines[ln].trim_start().starts_with('#') {
+            ln += 1;
+            continue;
+        }
+
+        let mut chars: Vec<char> = lines[ln].chars().collect();
+        let mut col = 0;
+
+        while col < chars.len() {
+            let c = chars[col];
+            let start = col;
+            let single = |kind| Token { kind, span: Span::new(ln + 1, start + 1, 1) };
+
+            match c {
+                c if c.is_whitespace() => col += 1,
+                '{' => { tokens.push(single(TokenKind::LBrace)); col += 1; }
+                '}' => { tokens.push(single(TokenKind::RBrace)); col += 1; }
+                '[' => { tokens.push(single(TokenKind::LBracket)); col += 1; }
+                ']' => { tokens.push(single(TokenKind::RBracket)); col += 1; }
+                ',' => { tokens.push(single(TokenKind::Comma)); col += 1; }
+                '=' => { tokens.push(single(TokenKind::Equals)); col += 1; }
+                _ if opens_block(&chars, col).is_some() => {
+                    let (opener, closer) = opens_block(&chars, col).unwrap();
+                    let after = col + opener.len();
+                    let extra: String = chars[after..].iter().collect();
+                    if !extra.trim().is_empty() {
+                        let span = Span::new(ln + 1, after + 1, chars.len() - after);
+                        warnings.push(
+                            FrsDiagnostic::warning(format!("text after `{}` was ignored", opener), span, lines[ln])
+                                .with_help(format!("the block starts on the next line and ends at `{}`", closer)),
+                        );
+                    }
+                    let span = Span::new(ln + 1, start + 1, opener.len());
+                    match read_block(&lines, ln + 1, closer) {
+                        Some((text, end_line, end_col)) => {
+                            tokens.push(Token { kind: TokenKind::Str(text), span });
+                            ln = end_line;
+                            chars = lines[ln].chars().collect();
+                            col = end_col;
+                        }
+                        None => {
+                            warnings.push(
+                                FrsDiagnostic::warning("unterminated text block", span, lines[ln])
+                                    .with_help(format!("add `{}` on a line of its own; the block ran to the end of the file", closer)),
+                            );
+                            let text = lines[ln + 1..].join("\n");
+                            tokens.push(Token { kind: TokenKind::Str(text), span });
+                            ln = lines.len() - 1;
+                            chars = lines[ln].chars().collect();
+                            col = chars.len();
+                        }
+                    }
+                }
+                '"' => match read_string(&lines, ln, col + 1) {
+                    Some((text, end_line, end_col)) => {
+                        let len = if end_line == ln { end_col - start } else { chars.len() - start };
+                        tokens.push(Token { kind: TokenKind::Str(text), span: Span::new(ln + 1, start + 1, len) });
+                        // Carry on after the closing quote, possibly on a later line
+                        if end_line != ln {
+                            ln = end_line;
+                            chars = lines[ln].chars().collect();
+                        }
+                        col = end_col;
+                    }
+                    None => {
+                        // Unterminated: keep the rest of this line as the string
+                        let text: String = chars[col + 1..].iter().collect();
+                        let span = Span::new(ln + 1, start + 1, chars.len() - start);
+                        warnings.push(
+                            FrsDiagnostic::warning("unterminated string", span, lines[ln])
+                                .with_help("add a closing `\"`; the rest of this line was used as the text"),
+                        );
+                        tokens.push(Token { kind: TokenKind::Str(text.trim_end().to_string()), span });
+                        col = chars.len();
+                    }
+                },
+                _ => {
+                    while col < chars.len() && !is_delimiter(chars[col]) {
+                        col += 1;
+                    }
+                    let word: String = chars[start..col].iter().collect();
+                    tokens.push(Token { kind: TokenKind::Word(word), span: Span::new(ln + 1, start + 1, col - start) });
+                }
+            }
+        }
+
+        tokens.push(Token { kind: TokenKind::Newline, span: Span::new(ln + 1, chars.len() + 1, 0) });
+        ln += 1;
+    }
+
+    (tokens, warnings)
+}
+
+/// Read a string body starting at `(ln, col)` (just past the opening
+/// quote). Returns the text and the position just past the closing quote,
+/// or `None` if the file ends first.
+fn read_string(lines: &[&str], ln: usize, col: usize) -> Option<(String, usize, usize)> {
+    let mut text = String::new();
+    let mut line = ln;
+    let mut chars: Vec<char> = lines[ln].chars().collect();
+    let mut i = col;
+
+    loop {
+        while i < chars.len() {
+            match chars[i] {
+                '"' => return Some((text, line, i + 1)),
+                '\\' if i + 1 < chars.len() => {
+                    match chars[i + 1] {
+                        'n' => text.push('\n'),
+                        't' => text.push('\t'),
+                        '"' => text.push('"'),
+                        '\\' => text.push('\\'),
+                        other => {
+                            // Unknown escapes are kept as written
+                            text.push('\\');
+                            text.push(other);
+                        }
+                    }
+                    i += 2;
+                    continue;
+                }
+                c => text.push(c),
+            }
+            i += 1;
+        }
+        line += 1;
+        if line >= lines.len() {
+            return None;
+        }
+        // Continuation lines are trimmed, like the rest of the script
+        let trimmed = lines[line].trim_end();
+        text = text.trim_end().to_string();
+        text.push('\n');
+        chars = trimmed.chars().collect();
+        i = chars.iter().take_while(|c| c.is_whitespace()).count();
+    }
+}
+
+/// `Some((opener, closer))` if a raw block starts at `col`.
+fn opens_block(chars: &[char], col: usize) -> Option<(&'static str, &'static str)> {
+    let rest: String = chars[col..].iter().take(3).collect();
+    match rest.as_str() {
+        "<<<" => Some(("<<<", ">>>")),
+        "\"\"\"" => Some(("\"\"\"", "\"\"\"")),
+        _ => None,
+    }
+}
+
+/// Read raw lines from `first` up to a line that is just `closer`
+/// (ignoring surrounding whitespace). Returns the text and the position
+/// just past the closer, or `None` if the file ends first.
+fn read_block(lines: &[&str], first: usize, closer: &str) -> Option<(String, usize, usize)> {
+    let end = (first..lines.len()).find(|&l| lines[l].trim() == closer)?;
+    let indent: String = lines[end].chars().take_while(|c| c.is_whitespace()).collect();
+
+    let text = lines[first..end]
+        .iter()
+        .map(|l| match l.strip_prefix(indent.as_str()) {
+            Some(rest) => rest,
+            // Less indented than the closer (or blank): drop what there is
+            None => l.trim_start(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some((text, end, indent.chars().count() + closer.len()))
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '"' | '{' | '}' | '[' | ']' | ',' | '=')
+}
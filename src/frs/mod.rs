@@ -1,7 +1,11 @@
 pub mod ast;
+pub mod diagnostic;
+pub mod lexer;
 pub mod parser;
 pub mod avatars;
 pub mod emojis;
 pub mod persist;
+pub mod update;
 
 pub use persist::persist_frs;
+pub use update::update_frs;
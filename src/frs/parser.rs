@@ -1,321 +1,501 @@
-use std::fs;
-use crate::frs::ast::{Thread, Message, ScriptItem, Command};
-use crate::frs::avatars::{
-    load_avatars, 
-};
-
-/// Pure parser: read .frs into a Thread struct (no side effects)
-pub fn parse_frs(path: &str) -> Thread {
-    let raw = fs::read_to_string(path).expect("❌ Could not read .frs file");
-    let lines: Vec<String> = raw
-        .lines()
-        .map(|l| l.trim().to_string())
-        .filter(|l| !l.is_empty() && !l.starts_with('#'))
-        .collect();
-
-    let mut i = 0usize;
-
-    // ---- header: new "Title"
-    let title = loop {
-        if i >= lines.len() {
-            panic!("❌ Missing `new \"Title\"` at top of file");
-        }
-        let line = &lines[i];
-        if line.starts_with("new ") {
-            break extract_quoted(line).unwrap_or_else(|| {
-                panic!("❌ Could not parse thread title from: {}", line)
-            });
-        }
-        i += 1;
-    };
-    let mut thread = Thread {
-        title,
-        tags: vec![],
-        items: vec![],
-    };
-    i += 1;
-
-    // ---- header meta (any order): user, tags ...
-    // We keep scanning header lines until the first content line ("jot"/"branch") appears.
-    let mut default_user: Option<String> = None;
+//! `.frs` parser: tokens from the [lexer](crate::frs::lexer) → [`Thread`].
+//!
+//! Each source line is one statement. Problems that only cost part of the
+//! script (a stray line, an unclosed block) are warnings kept on the
+//! returned thread; anything that leaves no sensible thread is an error.
 
-    while i < lines.len() {
-        let line = &lines[i];
-
-        // stop when content starts
-        if line.starts_with("jot") || line.starts_with("branch") {
-            break;
-        }
-
-        if line.starts_with("user") {
-            // Accept both: `user = name` and `user name`
-            if let Some(eq_pos) = line.find('=') {
-                // user = andrew
-                let val = line[eq_pos + 1..].trim();
-                if val.is_empty() {
-                    panic!("❌ Could not parse `user = <name>` line");
-                }
-                default_user = Some(val.to_string());
-            } else {
-                // user andrew
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() == 2 {
-                    default_user = Some(parts[1].to_string());
-                } else {
-                    panic!("❌ Could not parse `user <name>` line");
-                }
-            }
-            i += 1;
-            continue;
-        }
+use std::collections::HashMap;
+use std::fs;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde_json::Value;
+
+use crate::frs::ast::{Command, Message, ScriptItem, Thread};
+use crate::frs::diagnostic::{FrsDiagnostic, Span};
+use crate::frs::lexer::{lex, Token, TokenKind};
+
+/// Script commands executed by `fur run`.
+const COMMANDS: &[&str] = &["timeline", "tree", "status", "store"];
+
+/// Read and parse a script file.
+/// `avatars` supplies the `main` fallback when the script sets no `user`.
+pub fn parse_frs(path: &str, avatars: &Value) -> Result<Thread, Vec<FrsDiagnostic>> {
+    let source = fs::read_to_string(path).map_err(|e| {
+        vec![FrsDiagnostic::file_error(format!("cannot read {}: {}", path, e))]
+    })?;
+    parse_source(&source, avatars)
+}
 
-        if line.starts_with("tags") {
-            if let Some(tags) = parse_tags_line(line) {
-                thread.tags = tags;
-            }
-            i += 1;
-            continue;
+/// Parse script text. On success, warnings are left in `Thread::warnings`;
+/// on failure every diagnostic (errors and warnings) is returned.
+pub fn parse_source(source: &str, avatars: &Value) -> Result<Thread, Vec<FrsDiagnostic>> {
+    let (tokens, warnings) = lex(source);
+    let mut parser = Parser {
+        lines: source.lines().collect(),
+        statements: split_statements(tokens),
+        pos: 0,
+        diagnostics: warnings,
+        commands: vec![],
+        ids: HashMap::new(),
+    };
+    let thread = parser.parse(avatars);
+
+    let mut diagnostics = parser.diagnostics;
+    diagnostics.sort_by_key(|d| d.span.map(|s| (s.line, s.col)));
+    match thread {
+        Some(mut thread) if !diagnostics.iter().any(|d| d.is_error()) => {
+            thread.warnings = diagnostics;
+            Ok(thread)
         }
-
-        // Unknown header directive — stop treating as header block
-        break;
+        _ => Err(diagnostics),
     }
+}
 
-    // Fallback to avatars.json main if user not defined
-    let default_user = if let Some(u) = default_user {
-        u
-    } else {
-        let avatars = load_avatars();
-        if let Some(main) = avatars.get("main").and_then(|v| v.as_str()) {
-            main.to_string()
+/// Group tokens into lines, dropping empty ones.
+fn split_statements(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut out = Vec::new();
+    let mut current = Vec::new();
+    for token in tokens {
+        if token.kind == TokenKind::Newline {
+            if !current.is_empty() {
+                out.push(std::mem::take(&mut current));
+            }
         } else {
-            panic!("❌ Please define main avatar with `user = <name>` or set one with `fur avatar <name>`.");
+            current.push(token);
         }
-    };
+    }
+    if !current.is_empty() {
+        out.push(current);
+    }
+    out
+}
 
-    // ---- parse content into items
-    thread.items = parse_block(&lines, &mut i, false, &default_user);
-    thread
+struct Parser<'a> {
+    lines: Vec<&'a str>,
+    statements: Vec<Vec<Token>>,
+    pos: usize,
+    diagnostics: Vec<FrsDiagnostic>,
+    /// Commands from every nesting level, in source order
+    commands: Vec<Command>,
+    /// `id=` values seen so far → line
+    ids: HashMap<String, usize>,
 }
 
-// ------------------ Helpers ------------------
+impl Parser<'_> {
+    fn snippet(&self, span: Span) -> &str {
+        self.lines.get(span.line.saturating_sub(1)).copied().unwrap_or("")
+    }
 
-fn parse_block(
-    lines: &[String],
-    i: &mut usize,
-    stop_at_closing_brace: bool,
-    default_user: &str,
-) -> Vec<ScriptItem> {
-    let mut items: Vec<ScriptItem> = Vec::new();
+    fn warn(&mut self, message: impl Into<String>, span: Span, help: Option<&str>) {
+        let mut d = FrsDiagnostic::warning(message, span, self.snippet(span));
+        if let Some(help) = help {
+            d = d.with_help(help);
+        }
+        self.diagnostics.push(d);
+    }
 
-    while *i < lines.len() {
-        let line = &lines[*i];
+    fn error(&mut self, message: impl Into<String>, span: Span, help: Option<&str>) {
+        let mut d = FrsDiagnostic::error(message, span, self.snippet(span));
+        if let Some(help) = help {
+            d = d.with_help(help);
+        }
+        self.diagnostics.push(d);
+    }
 
-        if stop_at_closing_brace && line.starts_with('}') {
-            *i += 1;
-            break;
+    /// Warn about tokens left over at the end of a statement.
+    fn trailing(&mut self, rest: &[Token], after: &str) {
+        if let Some(first) = rest.first() {
+            let span = cover(rest);
+            self.warn(format!("unexpected {} after {}", first.kind.describe(), after), span, Some("it was ignored"));
         }
+    }
 
-        if line.starts_with("jot") {
-            if let Some(msg) = parse_jot_line(lines, i, default_user) {
-                items.push(ScriptItem::Message(msg));
+    fn parse(&mut self, avatars: &Value) -> Option<Thread> {
+        // ---- header: new "Title"
+        let mut title = None;
+        while self.pos < self.statements.len() {
+            let stmt = self.statements[self.pos].clone();
+            self.pos += 1;
+            if word(&stmt[0]) == Some("new") {
+                match stmt.get(1).map(|t| &t.kind) {
+                    Some(TokenKind::Str(t)) => {
+                        title = Some((t.clone(), stmt[0].span));
+                        self.trailing(&stmt[2..], "the title");
+                    }
+                    _ => {
+                        let span = stmt.get(1).map(|t| t.span).unwrap_or(stmt[0].span);
+                        self.error("thread title must be a quoted string", span, Some("write `new \"Title\"`"));
+                        return None;
+                    }
+                }
+                break;
             }
-            continue;
+            self.warn(
+                "line before `new \"Title\"` was ignored",
+                cover(&stmt),
+                Some("a script starts with `new \"Title\"`"),
+            );
         }
-
-        if is_command_line(line) {
-            let cmd = parse_command_line(line, *i + 1);
-            items.push(ScriptItem::Command(cmd));
-            *i += 1;
-            continue;
+        let Some((title, new_span)) = title else {
+            let span = Span::new(1, 1, self.lines.first().map(|l| l.chars().count()).unwrap_or(0));
+            self.error("missing `new \"Title\"`", span, Some("start the script with `new \"Title\"`"));
+            return None;
+        };
+
+        let mut thread = Thread { title, tags: vec![], items: vec![], avatars: Default::default(), warnings: vec![] };
+
+        // ---- header meta (any order): user, tags, avatar
+        let mut default_user: Option<String> = None;
+        while let Some(stmt) = self.statements.get(self.pos).cloned() {
+            match word(&stmt[0]) {
+                Some("user") => default_user = self.parse_user(&stmt).or(default_user),
+                Some("tags") => {
+                    if let Some(tags) = self.parse_tags(&stmt) {
+                        thread.tags = tags;
+                    }
+                }
+                Some("avatar") => {
+                    if let Some((name, emoji)) = self.parse_avatar(&stmt) {
+                        thread.avatars.insert(name, emoji);
+                    }
+                }
+                _ => break,
+            }
+            self.pos += 1;
         }
 
-        if is_branch_open(line) {
-            *i += 1; // consume "branch {"
-            if items.is_empty() {
-                eprintln!("❌ branch with no preceding jot at line {}", i);
-                let _ = parse_block(lines, i, true, default_user);
-                continue;
+        // Fallback to avatars.json main if user not defined
+        let default_user = match default_user.or_else(|| avatars.get("main").and_then(|v| v.as_str()).map(str::to_string)) {
+            Some(user) => user,
+            None => {
+                self.error(
+                    "no default avatar for this script",
+                    new_span,
+                    Some("add `user = <name>` below the title, or set one with `fur avatar <name>`"),
+                );
+                String::new()
             }
-            let children_block = parse_block(lines, i, true, default_user);
-            if let Some(ScriptItem::Message(last)) = items.last_mut() {
-                let children: Vec<Message> = children_block
-                    .into_iter()
-                    .filter_map(|si| {
-                        if let ScriptItem::Message(m) = si {
-                            Some(m)
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                last.branches.push(children.clone());
-                // Also flatten into children for compatibility
-                last.children.extend(children);
+        };
+
+        // ---- content
+        let messages = self.parse_block(&default_user, None);
+        thread.items = messages.into_iter().map(ScriptItem::Message).collect();
+        thread.items.extend(std::mem::take(&mut self.commands).into_iter().map(ScriptItem::Command));
+        thread.items.sort_by_key(ScriptItem::line);
+        Some(thread)
+    }
+
+    /// `user = name` or `user name`.
+    fn parse_user(&mut self, stmt: &[Token]) -> Option<String> {
+        let mut rest = &stmt[1..];
+        if rest.first().map(|t| &t.kind) == Some(&TokenKind::Equals) {
+            rest = &rest[1..];
+        }
+        match rest.first().map(|t| &t.kind) {
+            Some(TokenKind::Word(name)) | Some(TokenKind::Str(name)) => {
+                let name = name.clone();
+                self.trailing(&rest[1..], "the user name");
+                Some(name)
+            }
+            _ => {
+                self.error("could not parse `user` line", cover(stmt), Some("write `user = <name>`"));
+                None
             }
-            continue;
         }
+    }
 
-        if line.starts_with('}') {
-            *i += 1;
-            continue;
+    /// `avatar name = "emoji"` (the `=` is optional).
+    fn parse_avatar(&mut self, stmt: &[Token]) -> Option<(String, String)> {
+        let help = Some("write `avatar <name> = \"<emoji>\"`");
+        let Some(TokenKind::Word(name)) = stmt.get(1).map(|t| &t.kind) else {
+            self.warn("expected an avatar name after `avatar`", cover(stmt), help);
+            return None;
+        };
+        if name == "main" {
+            self.warn("`main` is reserved and can't be declared as an avatar", stmt[1].span, Some("it was ignored"));
+            return None;
         }
-
-        // Unknown/stray line — stop parsing at this level
-        if stop_at_closing_brace {
-            break;
-        } else {
-            eprintln!("⚠️ Unrecognized line: {}", line);
-            *i += 1;
+        let mut rest = &stmt[2..];
+        if rest.first().map(|t| &t.kind) == Some(&TokenKind::Equals) {
+            rest = &rest[1..];
+        }
+        match rest.first().map(|t| &t.kind) {
+            Some(TokenKind::Str(emoji)) | Some(TokenKind::Word(emoji)) => {
+                let declared = (name.clone(), emoji.clone());
+                self.trailing(&rest[1..], "the emoji");
+                Some(declared)
+            }
+            _ => {
+                self.warn(format!("missing emoji for avatar `{}`", name), cover(stmt), help);
+                None
+            }
         }
     }
 
-    items
-}
-
-fn is_branch_open(line: &str) -> bool {
-    line == "branch {" || line.starts_with("branch {")
-}
+    /// `tags = ["a", "b"]` (the `=` and the quotes are optional).
+    fn parse_tags(&mut self, stmt: &[Token]) -> Option<Vec<String>> {
+        let mut rest = &stmt[1..];
+        if rest.first().map(|t| &t.kind) == Some(&TokenKind::Equals) {
+            rest = &rest[1..];
+        }
+        if rest.first().map(|t| &t.kind) != Some(&TokenKind::LBracket) {
+            self.warn("expected `[` after `tags`", cover(stmt), Some("write `tags = [\"one\", \"two\"]`"));
+            return None;
+        }
 
-/// Collect multi-line quoted text starting at current line.
-/// Advances `i` until the closing `"` is found.
-fn collect_multiline_quoted(lines: &[String], i: &mut usize) -> Option<String> {
-    let mut buf = String::new();
-    let mut started = false;
-
-    while *i < lines.len() {
-        let line = &lines[*i];
-
-        if !started {
-            // find the first quote
-            if let Some(start) = line.find('"') {
-                started = true;
-                let after = &line[start + 1..];
-                if let Some(end) = after.find('"') {
-                    // opening and closing quote on same line
-                    buf.push_str(&after[..end]);
-                    *i += 1;
-                    return Some(buf);
-                } else {
-                    buf.push_str(after);
+        let mut tags = Vec::new();
+        for (i, token) in rest.iter().enumerate().skip(1) {
+            match &token.kind {
+                TokenKind::Str(t) | TokenKind::Word(t) => {
+                    if !t.trim().is_empty() {
+                        tags.push(t.trim().to_string());
+                    }
+                }
+                TokenKind::Comma => {}
+                TokenKind::RBracket => {
+                    self.trailing(&rest[i + 1..], "the tag list");
+                    return Some(tags);
+                }
+                other => {
+                    self.warn(format!("unexpected {} in tag list", other.describe()), token.span, None);
                 }
-            }
-        } else {
-            buf.push('\n');
-            if let Some(end) = line.find('"') {
-                buf.push_str(&line[..end]);
-                *i += 1;
-                return Some(buf);
-            } else {
-                buf.push_str(line);
             }
         }
-
-        *i += 1;
+        self.warn("unclosed tag list", cover(stmt), Some("add a closing `]`"));
+        Some(tags)
     }
 
-    None
-}
+    /// Statements up to the matching `}` (or the end of the file at the
+    /// top level). Returns the jots; commands go to `self.commands`.
+    fn parse_block(&mut self, default_user: &str, opened_at: Option<(&str, Span)>) -> Vec<Message> {
+        let mut messages: Vec<Message> = Vec::new();
+
+        while let Some(stmt) = self.statements.get(self.pos).cloned() {
+            self.pos += 1;
+            let head = &stmt[0];
+
+            match (&head.kind, word(head)) {
+                (TokenKind::RBrace, _) => {
+                    if opened_at.is_some() {
+                        self.trailing(&stmt[1..], "`}`");
+                        return messages;
+                    }
+                    self.warn("unmatched `}`", head.span, Some("there is no open `branch {` or `reply {` here"));
+                }
 
+                (_, Some("jot")) => {
+                    if let Some(msg) = self.parse_jot(&stmt, default_user) {
+                        messages.push(msg);
+                    }
+                }
 
-fn parse_tags_line(line: &str) -> Option<Vec<String>> {
-    let start = line.find('[')?;
-    let end = line.rfind(']')?;
-    let inner = &line[start + 1..end];
-    let tags = inner
-        .split(',')
-        .map(|s| s.trim().trim_matches('"').to_string())
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>();
-    Some(tags)
-}
+                (_, Some(block @ ("branch" | "reply"))) => {
+                    if stmt.get(1).map(|t| &t.kind) != Some(&TokenKind::LBrace) {
+                        self.warn(
+                            format!("expected `{{` after `{}`", block),
+                            cover(&stmt),
+                            Some(&format!("write `{} {{` and close it with `}}`", block)),
+                        );
+                        continue;
+                    }
+                    self.trailing(&stmt[2..], &format!("`{} {{`", block));
+                    let children = self.parse_block(default_user, Some((block, head.span)));
+                    match messages.last_mut() {
+                        Some(last) => {
+                            if block == "branch" {
+                                last.branches.push(children.clone());
+                            } else {
+                                last.replies.extend(children.clone());
+                            }
+                            // Also flatten into children for compatibility
+                            last.children.extend(children);
+                        }
+                        None => self.warn(
+                            format!("`{}` with no preceding jot", block),
+                            head.span,
+                            Some(&format!("a {} continues the jot right above it; this block was skipped", block)),
+                        ),
+                    }
+                }
 
-fn make_message(
-    avatar: &str,
-    text: Option<String>,
-    file: Option<String>,
-    attachment: Option<String>,
-) -> Message {
-    Message {
-        avatar: avatar.to_string(),
-        text,
-        file,
-        attachment,
-        children: vec![],
-        branches: vec![],
-    }
-}
+                (_, Some(name)) if COMMANDS.contains(&name) => {
+                    let args = stmt[1..]
+                        .iter()
+                        .filter_map(|t| match &t.kind {
+                            TokenKind::Word(w) | TokenKind::Str(w) => Some(w.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    self.commands.push(Command { name: name.to_string(), args, line_number: head.span.line });
+                }
 
-fn parse_text_jot(lines: &[String], i: &mut usize, avatar: &str) -> Option<Message> {
-    collect_multiline_quoted(lines, i)
-        .map(|text| make_message(avatar, Some(text), None, None))
-}
+                (_, Some(name @ ("new" | "user" | "tags" | "avatar"))) => {
+                    self.warn(
+                        format!("`{}` is only allowed at the top of the script", name),
+                        cover(&stmt),
+                        Some("it was ignored"),
+                    );
+                }
 
-fn parse_file_jot(line: &str, i: &mut usize, avatar: &str) -> Option<Message> {
-    let path = extract_quoted(line)
-        .or_else(|| line.split_whitespace().last().map(|s| s.to_string()))
-        .unwrap_or_default();
-    *i += 1;
-    Some(make_message(avatar, None, Some(path), None))
-}
+                _ => self.warn(
+                    "unrecognized line",
+                    cover(&stmt),
+                    Some("expected `jot`, `branch {`, `reply {`, `}` or a command (timeline, tree, status, store)"),
+                ),
+            }
+        }
 
-fn parse_attach_jot(line: &str, i: &mut usize, avatar: &str) -> Option<Message> {
-    let path = extract_quoted(line)
-        .or_else(|| line.split_whitespace().last().map(|s| s.to_string()))
-        .unwrap_or_default();
-    *i += 1;
-    Some(make_message(avatar, None, None, Some(path)))
-}
+        if let Some((block, span)) = opened_at {
+            self.warn(format!("unclosed `{} {{`", block), span, Some("add a `}` to end the block"));
+        }
+        messages
+    }
 
-fn parse_jot_line(lines: &[String], i: &mut usize, default_avatar: &str) -> Option<Message> {
-    let line = &lines[*i];
-    let mut parts = line.split_whitespace();
-    let first = parts.next()?;
-    if first != "jot" {
-        return None;
+    /// Accept an `id=` value unless it is malformed or already used.
+    fn check_id(&mut self, id: String, span: Span) -> Option<String> {
+        let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            self.warn(
+                format!("invalid message id `{}`", id),
+                span,
+                Some("ids may only contain letters, digits, `-` and `_`; a fresh id was used"),
+            );
+            return None;
+        }
+        if let Some(first) = self.ids.get(&id) {
+            let first = *first;
+            self.warn(
+                format!("duplicate message id `{}` (first used on line {})", id, first),
+                span,
+                Some("a fresh id was used for this jot"),
+            );
+            return None;
+        }
+        self.ids.insert(id.clone(), span.line);
+        Some(id)
     }
 
-    let second = parts.next().unwrap_or("");
+    /// `jot [avatar] ["text"] [--file <path>] [--attach <path>]`, with at
+    /// least one of the three, plus optional `@timestamp`, `id=…` and
+    /// `#tag` attributes. Flags and attributes may come before or after
+    /// the text.
+    fn parse_jot(&mut self, stmt: &[Token], default_user: &str) -> Option<Message> {
+        let mut rest = &stmt[1..];
+        let mut avatar = default_user.to_string();
+        if let Some(TokenKind::Word(w)) = rest.first().map(|t| &t.kind) {
+            let attribute = w.starts_with(['@', '#'])
+                || rest.get(1).map(|t| &t.kind) == Some(&TokenKind::Equals);
+            if !w.starts_with("--") && !attribute {
+                avatar = w.clone();
+                rest = &rest[1..];
+            }
+        }
 
-    // Case A: default avatar
-    if second == "--file" {
-        return parse_file_jot(line, i, default_avatar);
-    }
-    if second == "--attach" {
-        return parse_attach_jot(line, i, default_avatar);
-    }
-    if second.starts_with('"') {
-        return parse_text_jot(lines, i, default_avatar);
-    }
+        let mut message = Message {
+            avatar,
+            text: None,
+            file: None,
+            attachment: None,
+            children: vec![],
+            branches: vec![],
+            replies: vec![],
+            timestamp: None,
+            id: None,
+            tags: vec![],
+            line: stmt[0].span.line,
+        };
+
+        while let Some(token) = rest.first() {
+            match &token.kind {
+                TokenKind::Str(text) if message.text.is_none() => message.text = Some(text.clone()),
+                TokenKind::Word(flag) if flag == "--file" || flag == "--attach" => {
+                    let path = match rest.get(1).map(|t| &t.kind) {
+                        Some(TokenKind::Str(p)) | Some(TokenKind::Word(p)) => p.clone(),
+                        _ => {
+                            self.warn(format!("missing path after `{}`", flag), token.span, Some("this jot was skipped"));
+                            return None;
+                        }
+                    };
+                    if flag == "--file" {
+                        message.file = Some(path);
+                    } else {
+                        message.attachment = Some(path);
+                    }
+                    rest = &rest[1..];
+                }
+                TokenKind::Word(at) if at.len() > 1 && at.starts_with('@') => match parse_timestamp(&at[1..]) {
+                    Some(ts) => message.timestamp = Some(ts),
+                    None => self.warn(
+                        format!("could not read timestamp `{}`", at),
+                        token.span,
+                        Some("write e.g. `@2025-03-01T10:00Z`; the import time was used"),
+                    ),
+                },
+                TokenKind::Word(tag) if tag.len() > 1 && tag.starts_with('#') => {
+                    let tag = tag[1..].to_string();
+                    if !message.tags.contains(&tag) {
+                        message.tags.push(tag);
+                    }
+                }
+                TokenKind::Word(key) if key == "id" && rest.get(1).map(|t| &t.kind) == Some(&TokenKind::Equals) => {
+                    let value = match rest.get(2).map(|t| &t.kind) {
+                        Some(TokenKind::Word(v)) | Some(TokenKind::Str(v)) => v.clone(),
+                        _ => {
+                            self.warn("missing value after `id=`", cover(&rest[..2]), Some("it was ignored"));
+                            rest = &rest[2..];
+                            continue;
+                        }
+                    };
+                    let span = cover(&rest[..3]);
+                    rest = &rest[2..];
+                    message.id = self.check_id(value, span);
+                }
+                _ => break,
+            }
+            rest = &rest[1..];
+        }
 
-    // Case B: explicit avatar
-    let avatar = second.to_string();
-    if line.contains("--file") {
-        return parse_file_jot(line, i, &avatar);
-    }
-    if line.contains("--attach") {
-        return parse_attach_jot(line, i, &avatar);
+        if message.text.is_none() && message.file.is_none() && message.attachment.is_none() {
+            self.warn("jot has no text", cover(stmt), Some("write `jot \"text\"`; this jot was skipped"));
+            return None;
+        }
+
+        self.trailing(rest, "the jot");
+        Some(message)
     }
-    parse_text_jot(lines, i, &avatar)
 }
 
-
-fn extract_quoted(line: &str) -> Option<String> {
-    let start = line.find('"')?;
-    let end = line[start + 1..].find('"')? + start + 1;
-    Some(line[start + 1..end].to_string())
+/// RFC 3339, or the shorter `2025-03-01T10:00Z`, `2025-03-01T10:00`
+/// (UTC) and `2025-03-01` forms.
+pub fn parse_timestamp(s: &str) -> Option<String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.to_rfc3339());
+    }
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M%:z") {
+        return Some(dt.to_rfc3339());
+    }
+    let naive = s.strip_suffix('Z').unwrap_or(s);
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(naive, f).ok())
+        .or_else(|| NaiveDate::parse_from_str(naive, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+        .map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc).to_rfc3339())
 }
 
-fn is_command_line(line: &str) -> bool {
-    line.starts_with("timeline")
-        || line.starts_with("tree")
-        || line.starts_with("status")
-        || line.starts_with("store")
+fn word(token: &Token) -> Option<&str> {
+    match &token.kind {
+        TokenKind::Word(w) => Some(w),
+        _ => None,
+    }
 }
 
-fn parse_command_line(line: &str, line_number: usize) -> Command {
-    let mut parts = line.split_whitespace();
-    let name = parts.next().unwrap_or("").to_string();
-    let args = parts.map(|s| s.to_string()).collect();
-    Command { name, args, line_number }
+/// A span from the first token to the end of the last one on its line.
+fn cover(tokens: &[Token]) -> Span {
+    let first = tokens[0].span;
+    let end = tokens
+        .iter()
+        .filter(|t| t.span.line == first.line)
+        .map(|t| t.span.col + t.span.len)
+        .max()
+        .unwrap_or(first.col + first.len);
+    Span::new(first.line, first.col, end - first.col)
 }
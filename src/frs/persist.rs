@@ -1,239 +1,184 @@
 use uuid::Uuid;
-use chrono::Utc;
-use serde_json::{json, Value};
-use std::fs;
-use std::path::Path;
+use std::collections::HashSet;
 use std::io::{self, Write};
 
 use crate::frs::ast::{Thread, Message};
 use crate::frs::ast::ScriptItem;
+use crate::store::{short_id, FurStore, Position, StoreError, StoredMessage, StoredThread, EPHEMERAL_PREFIX};
 
 /// Persist a parsed Thread into .fur/threads + .fur/messages
-pub fn persist_frs(thread: &Thread) -> String {
-    let fur_dir = Path::new(".fur");
-    if !fur_dir.exists() {
-        panic!("🚨 .fur directory not initialized. Run `fur new` at least once.");
-    }
+pub fn persist_frs(store: &FurStore, thread: &Thread) -> Result<String, StoreError> {
+    let index = store.load_index()?;
 
     // --- Check if a thread with the same title already exists ---
-    let index_path = fur_dir.join("index.json");
-    let mut index_data: Value =
-        serde_json::from_str(&fs::read_to_string(&index_path).unwrap()).unwrap();
-
-    let mut overwrite = false;
     let mut old_thread_id: Option<String> = None;
 
-    if let Some(threads) = index_data["threads"].as_array() {
-        for tid in threads {
-            if let Some(tid_str) = tid.as_str() {
-                let tpath = fur_dir.join("threads").join(format!("{}.json", tid_str));
-                if let Ok(txt) = fs::read_to_string(&tpath) {
-                    if let Ok(tjson) = serde_json::from_str::<Value>(&txt) {
-                        if tjson["title"].as_str() == Some(&thread.title) {
-                            // Found duplicate title
-                            println!("⚠️ Thread with title \"{}\" already exists.", thread.title);
-                            print!("Overwrite? [Y/n]: ");
-                            io::stdout().flush().unwrap();
-
-                            let mut input = String::new();
-                            io::stdin().read_line(&mut input).unwrap();
-                            let response = input.trim().to_lowercase();
-
-                            if response.is_empty() || response == "y" || response == "yes" {
-                                overwrite = true;
-                                old_thread_id = Some(tid_str.to_string());
-                            } else {
-                                println!("🚫 Skipped importing thread \"{}\".", thread.title);
-                                return tid_str.to_string();
-                            }
-                        }
-                    }
-                }
-            }
+    if let Some(existing) = store.find_thread_by_title(&index, &thread.title) {
+        // Found duplicate title
+        println!("⚠️ Thread with title \"{}\" already exists.", thread.title);
+        print!("Overwrite? [Y/n]: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).unwrap();
+        let response = input.trim().to_lowercase();
+
+        if response.is_empty() || response == "y" || response == "yes" {
+            old_thread_id = Some(existing.id);
+        } else {
+            println!("🚫 Skipped importing thread \"{}\".", thread.title);
+            return Ok(existing.id);
         }
     }
 
+    // --- `id=` attributes must not clash with messages kept in the store ---
+    let replaced: HashSet<String> = match &old_thread_id {
+        Some(tid) => store.load_tree(&store.load_thread(tid)?).into_keys().collect(),
+        None => HashSet::new(),
+    };
+    let mut ids = Vec::new();
+    explicit_ids(&root_messages(thread), &mut ids);
+    if let Some(taken) = ids.iter().find(|id| store.message_exists(id) && !replaced.contains(*id)) {
+        return Err(StoreError::Invalid(format!(
+            "Message id {} is already used in this store. Drop its `id=` or pick another.",
+            taken
+        )));
+    }
+
     // --- If overwrite, delete old thread + messages ---
-    if overwrite {
-        if let Some(tid) = &old_thread_id {
-            delete_old_thread(tid);
-            if let Some(arr) = index_data["threads"].as_array_mut() {
-                arr.retain(|v| v.as_str() != Some(tid));
-            }
-        }
+    store.begin_op("run");
+    if let Some(tid) = &old_thread_id {
+        store.delete_thread(tid)?;
     }
+    let mut index = store.load_index()?;
+    let before = Position::of(&index);
+
+    merge_avatars(store, thread)?;
 
     // --- Now persist fresh thread ---
-    let thread_id = Uuid::new_v4().to_string();
-    let timestamp = Utc::now().to_rfc3339();
+    let mut stored = StoredThread::new(&thread.title);
+    stored.tags = thread.tags.clone();
 
     // Persist only the *root* jots; recursion handles nested branches
-    let root_ids = persist_level(
-        &thread.items.iter().filter_map(|item| {
-            if let ScriptItem::Message(m) = item { Some(m) } else { None }
-        }).cloned().collect::<Vec<_>>(),
-        None
-    );
-
-
-    let thread_json = json!({
-        "id": thread_id,
-        "created_at": timestamp,
-        "title": thread.title,
-        "tags": thread.tags,
-        "messages": root_ids, // only roots here
-    });
-
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-    fs::write(&thread_path, serde_json::to_string_pretty(&thread_json).unwrap())
-        .expect("❌ Could not write thread file");
+    stored.messages = persist_level(store, &root_messages(thread), None, true)?;
+    store.save_thread(&stored)?;
 
     // Update index.json
-    index_data["threads"].as_array_mut().unwrap().push(thread_id.clone().into());
-    index_data["active_thread"] = thread_id.clone().into();
-    index_data["current_message"] = Value::Null;
-    if index_data["schema_version"].as_str() == Some("0.1") {
-        index_data["schema_version"] = Value::String("0.2".to_string());
-    }
-
-    fs::write(&index_path, serde_json::to_string_pretty(&index_data).unwrap()).unwrap();
-
-    println!("🌱 Imported thread into .fur: {} — \"{}\"", &thread_id[..8], thread.title);
-    thread_id
+    index.threads.push(stored.id.clone());
+    store.switch_thread(&mut index, &stored.id)?;
+    store.save_index(&index)?;
+    let detail = match old_thread_id {
+        Some(old) => format!("{} (replaced {})", thread.title, short_id(&old)),
+        None => thread.title.clone(),
+    };
+    store.commit_op(detail.as_str())?;
+    store.append_reflog("run", before, Position::of(&index), detail)?;
+
+    println!("🌱 Imported thread into .fur: {} — \"{}\"", short_id(&stored.id), thread.title);
+    Ok(stored.id)
 }
 
 
 /// Ephemeral persist: writes a thread into `.fur/tmp/` for previews.
 /// Returns ephemeral thread_id.
-pub fn persist_ephemeral(thread: &Thread) -> String {
-    let fur_dir = Path::new(".fur/tmp");
-    if !fur_dir.exists() {
-        fs::create_dir_all(fur_dir).expect("❌ Could not create .fur/tmp/");
-    }
-
-    let thread_id = format!("ephemeral-{}", Uuid::new_v4().to_string());
-    let timestamp = Utc::now().to_rfc3339();
-
-    let root_ids = persist_level(
-        &thread.items.iter().filter_map(|item| {
-            if let ScriptItem::Message(m) = item { Some(m) } else { None }
-        }).cloned().collect::<Vec<_>>(),
-        None
-    );
-
-    let thread_json = json!({
-        "id": thread_id,
-        "created_at": timestamp,
-        "title": thread.title,
-        "tags": thread.tags,
-        "messages": root_ids,
-    });
-
-    let thread_path = fur_dir.join(format!("{}.json", thread_id));
-    fs::write(&thread_path, serde_json::to_string_pretty(&thread_json).unwrap())
-        .expect("❌ Could not write ephemeral thread file");
-
-    thread_id
+pub fn persist_ephemeral(store: &FurStore, thread: &Thread) -> Result<String, StoreError> {
+    let mut stored = StoredThread::new(&thread.title);
+    stored.id = format!("{}{}", EPHEMERAL_PREFIX, Uuid::new_v4());
+    stored.tags = thread.tags.clone();
+    // Fresh message ids, so a preview never overwrites stored messages
+    stored.messages = persist_level(store, &root_messages(thread), None, false)?;
+
+    // Ephemeral ids resolve into tmp/
+    store.save_thread(&stored)?;
+    Ok(stored.id)
 }
 
 /// Clean up ephemeral thread + messages
-pub fn cleanup_ephemeral(thread_id: &str) {
-    let fur_dir = Path::new(".fur/tmp");
-    let thread_path = fur_dir.join(format!("{}.json", thread_id));
-    let _ = fs::remove_file(thread_path);
-    // NOTE: if we want to also clean messages, we can follow `delete_message_recursive`.
+pub fn cleanup_ephemeral(store: &FurStore, thread_id: &str) -> Result<(), StoreError> {
+    store.delete_thread(thread_id).map(|_| ())
 }
 
 
 
-/// Delete an old thread and all its message files.
-fn delete_old_thread(thread_id: &str) {
-    let fur_dir = Path::new(".fur");
-    let thread_path = fur_dir.join("threads").join(format!("{}.json", thread_id));
-
-    if let Ok(content) = fs::read_to_string(&thread_path) {
-        if let Ok(thread_json) = serde_json::from_str::<Value>(&content) {
-            if let Some(msgs) = thread_json["messages"].as_array() {
-                for m in msgs {
-                    if let Some(mid) = m.as_str() {
-                        delete_message_recursive(mid, fur_dir);
-                    }
-                }
-            }
+/// Declared avatars fill gaps in avatars.json (existing ones win).
+pub(crate) fn merge_avatars(store: &FurStore, thread: &Thread) -> Result<(), StoreError> {
+    if thread.avatars.is_empty() {
+        return Ok(());
+    }
+    let mut avatars = store.load_avatars()?;
+    if let Some(map) = avatars.as_object_mut() {
+        let before = map.len();
+        for (name, emoji) in &thread.avatars {
+            map.entry(name.clone()).or_insert_with(|| emoji.clone().into());
+        }
+        if map.len() != before {
+            store.save_avatars(&avatars)?;
         }
     }
+    Ok(())
+}
 
-    let _ = fs::remove_file(thread_path);
+/// Top-level jots of a parsed script (commands are skipped).
+pub(crate) fn root_messages(thread: &Thread) -> Vec<Message> {
+    thread
+        .items
+        .iter()
+        .filter_map(|item| {
+            if let ScriptItem::Message(m) = item { Some(m.clone()) } else { None }
+        })
+        .collect()
 }
 
-/// Recursively delete a message and its children/branches.
-fn delete_message_recursive(msg_id: &str, fur_dir: &Path) {
-    let msg_path = fur_dir.join("messages").join(format!("{}.json", msg_id));
-    if let Ok(content) = fs::read_to_string(&msg_path) {
-        if let Ok(msg_json) = serde_json::from_str::<Value>(&content) {
-            // delete children
-            if let Some(children) = msg_json["children"].as_array() {
-                for c in children {
-                    if let Some(cid) = c.as_str() {
-                        delete_message_recursive(cid, fur_dir);
-                    }
-                }
-            }
-            // delete branches
-            if let Some(branches) = msg_json["branches"].as_array() {
-                for block in branches {
-                    if let Some(arr) = block.as_array() {
-                        for c in arr {
-                            if let Some(cid) = c.as_str() {
-                                delete_message_recursive(cid, fur_dir);
-                            }
-                        }
-                    }
-                }
-            }
+/// `id=` values anywhere in `msgs`.
+pub(crate) fn explicit_ids(msgs: &[Message], out: &mut Vec<String>) {
+    for m in msgs {
+        out.extend(m.id.clone());
+        for block in &m.branches {
+            explicit_ids(block, out);
         }
+        explicit_ids(&m.replies, out);
     }
-    let _ = fs::remove_file(msg_path);
 }
 
-/// Persist a list of messages that share the same parent.
+/// Persist a list of messages that share the same parent, honoring
+/// `@timestamp` and (with `keep_ids`) `id=` attributes.
 /// Returns the IDs of **these** messages (not descendants).
-fn persist_level(msgs: &[Message], parent: Option<String>) -> Vec<String> {
+fn persist_level(
+    store: &FurStore,
+    msgs: &[Message],
+    parent: Option<String>,
+    keep_ids: bool,
+) -> Result<Vec<String>, StoreError> {
     let mut ids_at_this_level: Vec<String> = Vec::new();
 
     for m in msgs {
-        let msg_id = Uuid::new_v4().to_string();
-
-        let mut branch_groups_ids: Vec<Vec<String>> = Vec::new();
-        let mut direct_children_ids: Vec<String> = Vec::new();
+        let mut stored = StoredMessage::new(&m.avatar, parent.clone());
+        if let (true, Some(id)) = (keep_ids, &m.id) {
+            stored.id = id.clone();
+        }
+        if let Some(ref ts) = m.timestamp {
+            stored.timestamp = ts.clone();
+        }
+        stored.name = Some(m.avatar.clone());
+        stored.text = m.text.clone();
+        stored.markdown = m.file.clone();
+        stored.attachment = m.attachment.clone();
+        stored.tags = m.tags.clone();
 
         for branch_block in &m.branches {
-            let group_ids = persist_level(branch_block, Some(msg_id.clone()));
+            let group_ids = persist_level(store, branch_block, Some(stored.id.clone()), keep_ids)?;
             if !group_ids.is_empty() {
-                direct_children_ids.extend(group_ids.clone());
-                branch_groups_ids.push(group_ids);
+                stored.children.extend(group_ids.clone());
+                stored.branches.push(group_ids);
             }
         }
 
-        let msg_json = json!({
-            "id": msg_id,
-            "avatar": m.avatar,
-            "name": m.avatar,
-            "text": m.text,
-            "markdown": m.file,
-            "attachment": m.attachment,
-            "parent": parent,
-            "children": direct_children_ids,
-            "branches": branch_groups_ids,
-            "timestamp": Utc::now().to_rfc3339(),
-        });
-
-        let path = Path::new(".fur/messages").join(format!("{}.json", msg_id));
-        fs::write(&path, serde_json::to_string_pretty(&msg_json).unwrap())
-            .expect("❌ Could not write message file");
-
-        ids_at_this_level.push(msg_id);
+        let reply_ids = persist_level(store, &m.replies, Some(stored.id.clone()), keep_ids)?;
+        stored.children.extend(reply_ids);
+
+        store.save_message(&stored)?;
+        ids_at_this_level.push(stored.id);
     }
 
-    ids_at_this_level
+    Ok(ids_at_this_level)
 }
@@ -0,0 +1,293 @@
+//! `fur run --update`: bring a stored thread in line with its script
+//! while keeping the ids of the messages that are still there.
+//!
+//! Script jots are matched to stored messages by `id=` first, then among
+//! the children of the same parent slot: identical content, then the
+//! next unclaimed message from the same avatar. Unmatched jots are added
+//! and unmatched messages removed.
+
+use std::collections::HashSet;
+use chrono::Utc;
+use colored::*;
+use serde_json::to_value;
+
+use crate::frs::ast::{Message, Thread};
+use crate::frs::persist::{explicit_ids, merge_avatars, persist_frs, root_messages};
+use crate::store::navigate::Tree;
+use crate::store::{short_id, FurStore, Position, Revision, StoreError, StoredMessage};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Added,
+    Changed,
+    Moved,
+    Removed,
+}
+
+impl Change {
+    fn symbol(self) -> ColoredString {
+        match self {
+            Change::Added => "+".green().bold(),
+            Change::Changed => "~".yellow().bold(),
+            Change::Moved => ">".cyan().bold(),
+            Change::Removed => "-".red().bold(),
+        }
+    }
+}
+
+struct Sync<'a> {
+    store: &'a FurStore,
+    /// The stored thread's messages before the update
+    old: Tree,
+    /// Stored ids the script names with `id=`; never matched structurally
+    reserved: HashSet<String>,
+    /// Stored ids already matched to a jot
+    claimed: HashSet<String>,
+    changes: Vec<(Change, String)>,
+    unchanged: usize,
+}
+
+/// Update the thread titled like the script in place, or import it if
+/// there is none. Returns the thread id. Callers should hold the store lock.
+pub fn update_frs(store: &FurStore, thread: &Thread) -> Result<String, StoreError> {
+    let mut index = store.load_index()?;
+    let Some(mut stored) = store.find_thread_by_title(&index, &thread.title) else {
+        return persist_frs(store, thread);
+    };
+    let old = store.load_tree(&stored);
+    let roots = root_messages(thread);
+
+    // `id=` attributes may name this thread's messages, or new ones
+    let mut ids = Vec::new();
+    explicit_ids(&roots, &mut ids);
+    if let Some(taken) = ids.iter().find(|id| !old.contains_key(*id) && store.message_exists(id)) {
+        return Err(StoreError::Invalid(format!(
+            "Message id {} is already used in this store. Drop its `id=` or pick another.",
+            taken
+        )));
+    }
+
+    store.begin_op("run");
+    let before = Position::of(&index);
+    merge_avatars(store, thread)?;
+
+    let mut sync = Sync {
+        store,
+        reserved: ids.into_iter().filter(|id| old.contains_key(id)).collect(),
+        old,
+        claimed: HashSet::new(),
+        changes: vec![],
+        unchanged: 0,
+    };
+    let old_roots = stored.messages.clone();
+    stored.messages = sync.level(&roots, None, &old_roots)?;
+    stored.tags = thread.tags.clone();
+
+    // Whatever the script no longer has, in thread order
+    let mut removed = Vec::new();
+    let mut to_visit: Vec<String> = old_roots.into_iter().rev().collect();
+    while let Some(id) = to_visit.pop() {
+        let Some(msg) = sync.old.get(&id) else { continue };
+        if !sync.claimed.contains(&id) && !removed.contains(&id) {
+            removed.push(id.clone());
+            sync.changes.push((Change::Removed, describe(msg)));
+        }
+        to_visit.extend(msg.child_ids().into_iter().rev());
+    }
+    if stored.cursor.as_ref().is_some_and(|c| removed.contains(c)) {
+        stored.cursor = None;
+    }
+    stored.marks.retain(|_, target| !removed.contains(target));
+    store.save_thread(&stored)?;
+
+    // Removed messages another thread still shares stay, and so do
+    // references to them
+    let reachable = store.reachable_messages()?;
+    let deleted: HashSet<String> = removed.iter().filter(|id| !reachable.contains(*id)).cloned().collect();
+    for id in &deleted {
+        if store.message_exists(id) {
+            store.delete_message_file(id)?;
+        }
+    }
+    for tid in index.threads.iter().filter(|t| **t != stored.id) {
+        let Ok(mut other) = store.load_thread(tid) else { continue };
+        let marks = other.marks.len();
+        other.marks.retain(|_, target| !deleted.contains(target));
+        let cursor_gone = other.cursor.as_ref().is_some_and(|c| deleted.contains(c));
+        if cursor_gone {
+            other.cursor = None;
+        }
+        if cursor_gone || other.marks.len() != marks {
+            store.save_thread(&other)?;
+        }
+    }
+
+    let mut index_changed = index.active_thread.as_deref() != Some(stored.id.as_str());
+    if index.current_message.as_ref().is_some_and(|c| deleted.contains(c)) {
+        index.current_message = None;
+        index_changed = true;
+    }
+    store.switch_thread(&mut index, &stored.id)?;
+    if index.current_message.as_ref().is_some_and(|c| removed.contains(c)) {
+        index.current_message = None;
+        index_changed = true;
+    }
+    let marks = index.marks.len();
+    index.marks.retain(|_, target| !deleted.contains(target));
+    if index_changed || index.marks.len() != marks {
+        store.save_index(&index)?;
+    }
+
+    let count = |kind: Change| sync.changes.iter().filter(|(k, _)| *k == kind).count();
+    let totals = format!(
+        "{} added, {} changed, {} moved, {} removed, {} unchanged",
+        count(Change::Added),
+        count(Change::Changed),
+        count(Change::Moved),
+        count(Change::Removed),
+        sync.unchanged
+    );
+    let detail = format!("{} (updated: {})", thread.title, totals);
+    store.commit_op(detail.as_str())?;
+    store.append_reflog("run", before, Position::of(&index), detail)?;
+
+    println!("🔁 Updated thread {} — \"{}\"", short_id(&stored.id), thread.title);
+    for (kind, line) in &sync.changes {
+        println!("  {} {}", kind.symbol(), line);
+    }
+    if sync.changes.is_empty() {
+        println!("{}", "  Nothing changed.".bright_black());
+    }
+    println!("{}", totals.bright_black());
+    Ok(stored.id)
+}
+
+impl Sync<'_> {
+    fn free(&self, id: &str) -> bool {
+        !self.claimed.contains(id) && !self.reserved.contains(id)
+    }
+
+    /// Sync the jots sharing one parent slot against the stored ids that
+    /// filled it. Returns the ids now in the slot.
+    fn level(&mut self, msgs: &[Message], parent: Option<&str>, candidates: &[String]) -> Result<Vec<String>, StoreError> {
+        let mut matched: Vec<Option<String>> = vec![None; msgs.len()];
+
+        // By id
+        for (slot, m) in matched.iter_mut().zip(msgs) {
+            if let Some(id) = m.id.as_ref().filter(|id| self.old.contains_key(*id)) {
+                self.claimed.insert(id.clone());
+                *slot = Some(id.clone());
+            }
+        }
+
+        // By identical content, then by avatar and order. Jots with an
+        // `id=` of their own are always new.
+        type Same = fn(&Message, &StoredMessage) -> bool;
+        let passes: [Same; 2] = [same_content, |m, s| m.avatar == s.avatar];
+        for same in passes {
+            for (slot, m) in matched.iter_mut().zip(msgs) {
+                if slot.is_some() || m.id.is_some() {
+                    continue;
+                }
+                let found = candidates.iter().find(|c| self.free(c) && self.old.get(*c).is_some_and(|s| same(m, s)));
+                if let Some(id) = found.cloned() {
+                    self.claimed.insert(id.clone());
+                    *slot = Some(id);
+                }
+            }
+        }
+
+        msgs.iter().zip(matched).map(|(m, existing)| self.apply(m, parent, existing)).collect()
+    }
+
+    /// Write one jot over its matched message (or a new one), then its
+    /// children. A text change keeps the old text as a revision; other
+    /// changes (avatar, tags, attachment) are applied without one, since
+    /// a `Revision` only holds text.
+    fn apply(&mut self, m: &Message, parent: Option<&str>, existing: Option<String>) -> Result<String, StoreError> {
+        let old = existing.and_then(|id| self.old.get(&id).cloned());
+        let mut msg = match old {
+            Some(ref o) => o.clone(),
+            None => {
+                let mut fresh = StoredMessage::new(&m.avatar, None);
+                if let Some(ref id) = m.id {
+                    fresh.id = id.clone();
+                }
+                fresh
+            }
+        };
+
+        if let Some(ref o) = old {
+            if o.text.is_some() && o.text != m.text {
+                msg.revisions.push(Revision {
+                    timestamp: Utc::now().to_rfc3339(),
+                    text: o.text.clone(),
+                    markdown_contents: None,
+                });
+            }
+        }
+        msg.avatar = m.avatar.clone();
+        msg.name = Some(m.avatar.clone());
+        msg.text = m.text.clone();
+        msg.markdown = m.file.clone();
+        msg.attachment = m.attachment.clone();
+        msg.tags = m.tags.clone();
+        if let Some(ref ts) = m.timestamp {
+            msg.timestamp = ts.clone();
+        }
+        msg.parent = parent.map(str::to_string);
+
+        // Children, matched against what filled the same slots before
+        let (old_branches, old_replies) = match old {
+            Some(ref o) => (o.branches.clone(), replies_of(o)),
+            None => (vec![], vec![]),
+        };
+        msg.children.clear();
+        msg.branches.clear();
+        for (k, block) in m.branches.iter().enumerate() {
+            let group = self.level(block, Some(&msg.id), old_branches.get(k).map(Vec::as_slice).unwrap_or(&[]))?;
+            if !group.is_empty() {
+                msg.children.extend(group.clone());
+                msg.branches.push(group);
+            }
+        }
+        let replies = self.level(&m.replies, Some(&msg.id), &old_replies)?;
+        msg.children.extend(replies);
+
+        match old {
+            None => self.changes.push((Change::Added, describe(&msg))),
+            Some(ref o) => {
+                if !same_stored_content(o, &msg) {
+                    self.changes.push((Change::Changed, describe(&msg)));
+                } else if o.parent != msg.parent {
+                    self.changes.push((Change::Moved, describe(&msg)));
+                } else {
+                    self.unchanged += 1;
+                }
+                if to_value(o).ok() == to_value(&msg).ok() {
+                    return Ok(msg.id);
+                }
+            }
+        }
+        self.store.save_message(&msg)?;
+        Ok(msg.id)
+    }
+}
+
+fn same_content(m: &Message, s: &StoredMessage) -> bool {
+    m.avatar == s.avatar && m.text == s.text && m.file == s.markdown && m.attachment == s.attachment
+}
+
+fn same_stored_content(a: &StoredMessage, b: &StoredMessage) -> bool {
+    (&a.avatar, &a.text, &a.markdown, &a.attachment, &a.tags, &a.timestamp)
+        == (&b.avatar, &b.text, &b.markdown, &b.attachment, &b.tags, &b.timestamp)
+}
+
+/// Children outside every branch group.
+fn replies_of(msg: &StoredMessage) -> Vec<String> {
+    msg.children.iter().filter(|c| !msg.branches.iter().flatten().any(|b| b == *c)).cloned().collect()
+}
+
+fn describe(msg: &StoredMessage) -> String {
+    format!("{} [{}] {}", short_id(&msg.id), msg.avatar, msg.preview())
+}
@@ -1,3 +1,4 @@
 pub mod commands;
 pub mod frs;
 pub mod renderer;
+pub mod store;
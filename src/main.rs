@@ -1,11 +1,9 @@
-mod commands;
-mod renderer;
-mod frs;
-
 use clap::{Parser, Subcommand, CommandFactory};
 use clap_complete::{generate, shells::{Bash, Zsh, Fish}};
 use std::io;
-use crate::commands::{
+use std::path::PathBuf;
+use fur_cli::store::{FurStore, StoreError};
+use fur_cli::commands::{
     avatar,
     jot::{self, JotArgs},
     chat,
@@ -18,6 +16,19 @@ use crate::commands::{
     new,
     thread,
     run,
+    migrate::{self, MigrateArgs},
+    doctor::{self, DoctorArgs},
+    gc::{self, GcArgs},
+    edit::{self, EditArgs},
+    show::{self, ShowArgs},
+    rm::{self, RmArgs},
+    restore::{self, RestoreArgs},
+    mv::{self, MvArgs},
+    merge::{self, MergeArgs},
+    mark::{self, MarkArgs},
+    reflog::{self, ReflogArgs},
+    undo::{self, UndoArgs},
+    branch::{self, BranchArgs},
 };
 
 #[derive(Parser)]
@@ -27,6 +38,10 @@ use crate::commands::{
     long_about = "Track, branch, and link your conversations, especially AI chats, using local files and JSON. Think of it like git for your ideas."
 )]
 struct Cli {
+    /// Use this .fur directory instead of searching upward ($FUR_DIR also works)
+    #[arg(long, global = true, value_name = "PATH")]
+    fur_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -66,12 +81,17 @@ enum Commands {
     /// Manage threads (list or switch)
     Thread(thread::ThreadArgs),
 
-    /// Fork the current message into a new thread
+    /// Fork the active thread (or, with --at, a message and its history) into a new thread
     Fork {
-        /// ID of the message to fork from (optional)
-        #[arg(short, long, default_value = "")]
+        /// ID of the thread to copy whole (defaults to the active thread)
+        #[arg(short, long, default_value = "", conflicts_with = "at")]
         id: String,
 
+        /// Fork from a message: copy only its root-to-message lineage
+        /// (defaults to the current message)
+        #[arg(long, value_name = "MSG", num_args = 0..=1)]
+        at: Option<Option<String>>,
+
         /// Optional custom title for the new fork
         #[arg(short, long)]
         title: Option<String>,
@@ -97,10 +117,60 @@ enum Commands {
     /// Run an .frs script (import + execute)
     Run {
         path: String,
+
+        /// Update the thread with the same title in place, keeping the ids
+        /// of unchanged messages, instead of replacing it
+        #[arg(long)]
+        update: bool,
     },
 
     /// Save threads/messages
     Save(SaveArgs),
+
+    /// Upgrade .fur/ to the current schema version
+    Migrate(MigrateArgs),
+
+    /// Check .fur/ for broken links and optionally repair them
+    Doctor(DoctorArgs),
+
+    /// Delete messages no thread can reach, plus preview leftovers
+    Gc(GcArgs),
+
+    /// Edit a message (or its linked markdown) in $EDITOR
+    Edit(EditArgs),
+
+    /// Show a single message, optionally with its revisions
+    Show(ShowArgs),
+
+    /// Remove a message (or subtree with --recursive) to the trash
+    Rm(RmArgs),
+
+    /// Bring back a message removed with `fur rm` (lists the trash if no id)
+    Restore(RestoreArgs),
+
+    /// Move a message (and its replies) to a new parent or thread
+    Mv(MvArgs),
+
+    /// Graft another thread's messages into this one as new branches
+    Merge(MergeArgs),
+
+    /// Bookmark a message by name (per thread, or --global)
+    Mark(MarkArgs),
+
+    /// List bookmarks
+    Marks {},
+
+    /// History of cursor moves and changes (return with `jump --reflog N`)
+    Reflog(ReflogArgs),
+
+    /// Revert the last jot, rm, mv, fork, thread rename/rm or .frs import
+    Undo(UndoArgs),
+
+    /// Re-apply what `fur undo` reverted
+    Redo(UndoArgs),
+
+    /// Open a new branch group under a message for the following jots
+    Branch(BranchArgs),
 }
 
 
@@ -112,15 +182,24 @@ enum AvatarAction {
 
 
 fn main() {
+    if let Err(e) = dispatch() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn dispatch() -> Result<(), StoreError> {
     let args: Vec<String> = std::env::args().collect();
 
     // === Shortcut: fur script.frs
     if args.len() == 2 && args[1].ends_with(".frs") {
-        run::run_frs(&args[1]);
-        return;
+        let store = FurStore::open(FurStore::locate(None))?;
+        return run::run_frs(&store, &args[1]);
     }
 
     let cli = Cli::parse();
+    let root = FurStore::locate(cli.fur_dir.as_deref());
+    let open = || FurStore::open(&root);
 
     match cli.command {
         Commands::Completions { shell } => {
@@ -131,55 +210,83 @@ fn main() {
                 "fish" => generate(Fish, &mut cmd, "fur", &mut io::stdout()),
                 _ => eprintln!("Unsupported shell: {}", shell),
             }
+            Ok(())
         }
 
 
         Commands::Avatar { action, view: _ } => {
             match action {
-                Some(AvatarAction::New) => avatar::run_avatar_new(),
-                None => avatar::run_avatar(), // default and --view both land here
+                Some(AvatarAction::New) => avatar::run_avatar_new(&open()?),
+                None => avatar::run_avatar(&open()?), // default and --view both land here
             }
         }
 
 
-        Commands::New { name } => new::run_new(name),
+        Commands::New { name } => new::run_new(&root, name),
 
         Commands::Status {} => {
             let args = status::StatusArgs { thread_override: None };
-            status::run_status(args);
+            status::run_status(&open()?, args)
         }
 
 
-        Commands::Thread(args) => thread::run_thread(args),
+        Commands::Thread(args) => thread::run_thread(&open()?, args),
 
-        Commands::Fork { id, title } => {
-            if id.is_empty() {
-                fork::run_fork_from_active(title.clone());
+        Commands::Fork { id, at, title } => {
+            if let Some(at) = at {
+                fork::run_fork_at(&open()?, at, title)
+            } else if id.is_empty() {
+                fork::run_fork_from_active(&open()?, title)
             } else {
-                fork::run_fork(&id, title.clone());
+                let store = open()?;
+                let id = store.resolve_thread(&store.load_index()?, &id)?;
+                fork::run_fork(&store, &id, title)
             }
         }
 
-        Commands::Jump(args) => {
-            if let Err(e) = jump::run_jump(args) {
-                eprintln!("Error: {}", e);
-            }
-        }
+        Commands::Jump(args) => jump::run_jump(&open()?, args),
 
-        Commands::Jot(args) => jot::run_jot(args),
+        Commands::Jot(args) => jot::run_jot(&open()?, args),
         
-        Commands::Chat { avatar } => {
-            chat::run_chat(avatar);
-        }
+        Commands::Chat { avatar } => chat::run_chat(&open()?, avatar),
 
-        Commands::Timeline(args) => timeline::run_timeline(args),
+        Commands::Timeline(args) => timeline::run_timeline(&open()?, args),
 
-        Commands::Tree(args) => tree::run_tree(args),
+        Commands::Tree(args) => tree::run_tree(&open()?, args),
 
-        Commands::Run { path } => {
-            run::run_frs(&path);
-        }
+        Commands::Run { path, update: false } => run::run_frs(&open()?, &path),
+        Commands::Run { path, update: true } => run::run_frs_update(&open()?, &path),
+
+        Commands::Save(args) => save::run_save(&open()?, args),
+
+        Commands::Migrate(args) => migrate::run_migrate(&root, args),
+
+        Commands::Doctor(args) => doctor::run_doctor(&open()?, args),
+
+        Commands::Gc(args) => gc::run_gc(&open()?, args),
+
+        Commands::Edit(args) => edit::run_edit(&open()?, args),
+
+        Commands::Show(args) => show::run_show(&open()?, args),
+
+        Commands::Rm(args) => rm::run_rm(&open()?, args),
+
+        Commands::Restore(args) => restore::run_restore(&open()?, args),
+
+        Commands::Mv(args) => mv::run_mv(&open()?, args),
+
+        Commands::Merge(args) => merge::run_merge(&open()?, args),
+
+        Commands::Mark(args) => mark::run_mark(&open()?, args),
+
+        Commands::Marks {} => mark::run_marks(&open()?),
+
+        Commands::Reflog(args) => reflog::run_reflog(&open()?, args),
+
+        Commands::Undo(args) => undo::run_undo(&open()?, args),
+
+        Commands::Redo(args) => undo::run_redo(&open()?, args),
 
-        Commands::Save(args) => save::run_save(args),
+        Commands::Branch(args) => branch::run_branch(&open()?, args),
     }
 }
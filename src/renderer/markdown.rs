@@ -3,17 +3,18 @@ use std::path::Path;
 use serde_json::Value;
 
 use crate::commands::timeline::TimelineArgs;
-use crate::renderer::utils::load_message;
+use crate::renderer::utils::{load_message, mark_label};
+use crate::store::FurStore;
 
 pub fn render_message_md(
-    fur_dir: &Path,
+    store: &FurStore,
     msg_id: &str,
     label: String,
     args: &TimelineArgs,
     avatars: &Value,
     out: &mut String,
 ) {
-    let Some(msg) = load_message(fur_dir, msg_id, avatars) else { return };
+    let Some(msg) = load_message(store, msg_id, avatars) else { return };
 
     if let Some(att) = msg.attachment {
         if att.ends_with(".png")
@@ -37,6 +38,16 @@ pub fn render_message_md(
     out.push_str(&format!("**{} [{}]:** {}\n", msg.name, msg.emoji, msg.text));
     out.push_str(&format!("_{} {} - {}_\n\n", msg.date_str, msg.time_str, label));
 
+    if let Some(mark) = mark_label(&args.marks, msg_id) {
+        out.push_str(&format!("_{}_\n\n", mark));
+    }
+
+    if args.verbose {
+        if let Some(ref when) = msg.edited_at {
+            out.push_str(&format!("_✏️ edited {}× (last {})_\n\n", msg.edits, when));
+        }
+    }
+
     if args.verbose || args.contents {
         if let Some(path_str) = msg.markdown {
             if let Ok(contents) = fs::read_to_string(path_str) {
@@ -50,7 +61,7 @@ pub fn render_message_md(
         let branch_label = format!("{} - Branch {}", label, bi + 1);
 
         for cid in block {
-            render_message_md(fur_dir, cid, branch_label.clone(), args, avatars, out);
+            render_message_md(store, cid, branch_label.clone(), args, avatars, out);
         }
     }
 }
@@ -1,11 +1,11 @@
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
 use std::process::Command;
 use serde_json::Value;
 
 use crate::commands::timeline::TimelineArgs;
 use crate::renderer::utils::load_message;
+use crate::store::FurStore;
 
 /// LaTeX preamble with fixes for Pandoc output + math + images
 fn latex_preamble(thread_title: &str) -> String {
@@ -56,15 +56,14 @@ fn strip_emojis(input: &str) -> String {
 
 /// Render a single message (recursively) into LaTeX
 pub fn render_message_tex(
-    fur_dir: &Path,
+    store: &FurStore,
     msg_id: &str,
     label: String,        // e.g. "Root", "Root - Branch 1"
     args: &TimelineArgs,
     avatars: &Value,
     tex_out: &mut File,
-    depth: usize,         // branch depth
 ) {
-    let Some(msg) = load_message(fur_dir, msg_id, avatars) else { return };
+    let Some(msg) = load_message(store, msg_id, avatars) else { return };
 
     // Escape LaTeX special characters
     let escape = |s: &str| {
@@ -93,7 +92,7 @@ pub fn render_message_tex(
 
             // Try to render markdown as LaTeX
             match Command::new("pandoc")
-                .args(&["-f", "markdown", "-t", "latex", &path_str])
+                .args(["-f", "markdown", "-t", "latex", &path_str])
                 .output()
             {
                 Ok(output) if output.status.success() => {
@@ -154,7 +153,7 @@ pub fn render_message_tex(
         let branch_label = format!("{} - Branch {}", label, bi + 1);
 
         for cid in block {
-            render_message_tex(fur_dir, cid, branch_label.clone(), args, avatars, tex_out, depth + 1);
+            render_message_tex(store, cid, branch_label.clone(), args, avatars, tex_out);
         }
     }
 }
@@ -162,9 +161,9 @@ pub fn render_message_tex(
 
 /// Export a full thread to LaTeX and compile to PDF
 pub fn export_to_pdf(
-    fur_dir: &Path,
+    store: &FurStore,
     thread_title: &str,
-    root_msgs: &[Value],
+    root_msgs: &[String],
     args: &TimelineArgs,
     avatars: &Value,
     out_path: &str,
@@ -177,9 +176,7 @@ pub fn export_to_pdf(
 
     // Write messages
     for mid in root_msgs {
-        if let Some(mid_str) = mid.as_str() {
-            render_message_tex(fur_dir, mid_str, "Root".to_string(), args, avatars, &mut file, 0);
-        }
+        render_message_tex(store, mid, "Root".to_string(), args, avatars, &mut file);
     }
 
     // End document
@@ -1,19 +1,19 @@
 use std::fs;
-use std::path::Path;
 use serde_json::Value;
 use colored::*;
 
 use crate::commands::timeline::TimelineArgs;
-use crate::renderer::utils::load_message;
+use crate::renderer::utils::{load_message, mark_label};
+use crate::store::FurStore;
 
 pub fn render_message(
-    fur_dir: &Path,
+    store: &FurStore,
     msg_id: &str,
     label: String,
     args: &TimelineArgs,
     avatars: &Value,
 ) {
-    let Some(msg) = load_message(fur_dir, msg_id, avatars) else { return };
+    let Some(msg) = load_message(store, msg_id, avatars) else { return };
     
     if let Some(att) = msg.attachment {
         println!("{}🖼️ [Attachment: {}]\n", " ".repeat(4), att);
@@ -29,6 +29,16 @@ pub fn render_message(
     );
     println!("{}\n", msg.text.white());
 
+    if let Some(mark) = mark_label(&args.marks, msg_id) {
+        println!("{}\n", mark.bright_magenta());
+    }
+
+    if args.verbose {
+        if let Some(ref when) = msg.edited_at {
+            println!("{}\n", format!("✏️ edited {}× (last {})", msg.edits, when).bright_black());
+        }
+    }
+
     if args.verbose || args.contents {
         if let Some(path_str) = msg.markdown {
             if let Ok(contents) = fs::read_to_string(path_str) {
@@ -42,7 +52,7 @@ pub fn render_message(
         let branch_label = format!("{} - Branch {}", label, bi + 1);
 
         for cid in block {
-            render_message(fur_dir, cid, branch_label.clone(), args, avatars);
+            render_message(store, cid, branch_label.clone(), args, avatars);
         }
     }
 }
@@ -1,9 +1,9 @@
-use std::fs;
-use std::path::Path;
+use std::collections::HashMap;
 use serde_json::Value;
 use chrono::{DateTime, FixedOffset, Local};
 
 use crate::frs::avatars::resolve_avatar;
+use crate::store::FurStore;
 
 /// Struct holding normalized message info
 #[allow(dead_code)]
@@ -18,56 +18,46 @@ pub struct MessageInfo {
     #[allow(dead_code)]
     pub children: Vec<String>,
     pub branches: Vec<Vec<String>>,
+    /// How many times the message was edited
+    pub edits: usize,
+    /// Local date + time of the last edit
+    pub edited_at: Option<String>,
+}
+
+/// Split an RFC 3339 timestamp into local date and time strings.
+/// Unparseable values are returned as-is in the date slot.
+pub fn format_timestamp(ts: &str) -> (String, String) {
+    match ts.parse::<DateTime<FixedOffset>>() {
+        Ok(dt) => {
+            let local_dt = dt.with_timezone(&Local);
+            (local_dt.format("%Y-%m-%d").to_string(), local_dt.format("%H:%M:%S").to_string())
+        }
+        Err(_) => (ts.to_string(), "".to_string()),
+    }
 }
 
 
+/// "🔖 name, other" for a bookmarked message, if it has any marks.
+pub fn mark_label(marks: &HashMap<String, Vec<String>>, msg_id: &str) -> Option<String> {
+    marks.get(msg_id).map(|names| format!("🔖 {}", names.join(", ")))
+}
 
 /// Load and normalize a message JSON
-pub fn load_message(fur_dir: &Path, msg_id: &str, avatars: &Value) -> Option<MessageInfo> {
-    let msg_path = fur_dir.join("messages").join(format!("{}.json", msg_id));
-    let msg_content = fs::read_to_string(&msg_path).ok()?;
-    let msg_json: Value = serde_json::from_str(&msg_content).ok()?;
+pub fn load_message(store: &FurStore, msg_id: &str, avatars: &Value) -> Option<MessageInfo> {
+    let msg = store.load_message(msg_id).ok()?;
 
     // Timestamp
-    let raw_time = msg_json["timestamp"].as_str().unwrap_or("???");
-    let (date_str, time_str) = if let Ok(dt) = raw_time.parse::<DateTime<FixedOffset>>() {
-        let local_dt = dt.with_timezone(&Local);
-        (local_dt.format("%Y-%m-%d").to_string(), local_dt.format("%H:%M:%S").to_string())
-    } else {
-        (raw_time.to_string(), "".to_string())
-    };
+    let (date_str, time_str) = format_timestamp(&msg.timestamp);
+    let edited_at = msg.revisions.last().map(|r| {
+        let (d, t) = format_timestamp(&r.timestamp);
+        format!("{} {}", d, t)
+    });
 
     // Avatar
-    let avatar_key = msg_json["avatar"].as_str().unwrap_or("???");
-    let (name, emoji) = resolve_avatar(avatars, avatar_key);
+    let (name, emoji) = resolve_avatar(avatars, &msg.avatar);
 
     // Text & markdown
-    let text = msg_json["text"].as_str().unwrap_or("<no content>").to_string();
-    let markdown = msg_json["markdown"].as_str().map(|s| s.to_string());
-
-    // Children
-    let children = msg_json["children"]
-        .as_array()
-        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-        .unwrap_or_else(Vec::new);
-
-    // Branches
-    let branches = msg_json["branches"]
-        .as_array()
-        .map(|outer| {
-            outer.iter()
-                .filter_map(|block| {
-                    block.as_array().map(|arr| {
-                        arr.iter()
-                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                            .collect::<Vec<_>>()
-                    })
-                })
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_else(Vec::new);
-
-    let attachment = msg_json["attachment"].as_str().map(|s| s.to_string());
+    let text = msg.text.unwrap_or_else(|| "<no content>".to_string());
 
     Some(MessageInfo {
         date_str,
@@ -75,9 +65,12 @@ pub fn load_message(fur_dir: &Path, msg_id: &str, avatars: &Value) -> Option<Mes
         name,
         emoji,
         text,
-        markdown,
-        attachment,
-        children,
-        branches,
+        // Resolved against the project so it reads from any subfolder
+        markdown: msg.markdown.map(|p| store.project_path(&p).to_string_lossy().to_string()),
+        attachment: msg.attachment,
+        children: msg.children,
+        branches: msg.branches,
+        edits: msg.revisions.len(),
+        edited_at,
     })
 }
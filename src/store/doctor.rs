@@ -0,0 +1,331 @@
+//! Integrity checks over the whole `.fur/` graph, plus repairs.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{short_id, FurStore, Index, StoreError};
+
+/// One inconsistency found by [`FurStore::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// `index.threads` lists a thread whose file is gone
+    MissingThread { thread: String },
+    /// `index.active_thread` points at a thread that doesn't exist (and
+    /// isn't listed, or it would be a `MissingThread`)
+    ActiveThreadMissing { thread: String },
+    /// `index.active_thread` exists but `index.threads` doesn't list it
+    ActiveThreadUnlisted { thread: String },
+    /// `index.current_message` points at a message that doesn't exist
+    CurrentMessageMissing { message: String },
+    /// A thread's root list names a missing message file
+    MissingRoot { thread: String, message: String },
+    /// `children`/`branches` names a missing message file
+    DanglingChild { thread: String, parent: String, child: String },
+    /// A message's `parent` disagrees with where it is listed
+    ParentMismatch {
+        thread: String,
+        message: String,
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    /// A branch member that is missing from `children`
+    BranchNotInChildren { thread: String, parent: String, child: String },
+    /// A `jot --markdown` file that no longer exists
+    MissingMarkdown { thread: String, message: String, path: String },
+    /// A bookmark (thread-level, or global when `thread` is `None`) whose
+    /// message is gone
+    DanglingMark { thread: Option<String>, name: String, message: String },
+    /// A thread's saved cursor points at a message that doesn't exist
+    DanglingCursor { thread: String, message: String },
+}
+
+impl Issue {
+    /// Whether `fur doctor --fix` knows how to repair this.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self, Issue::MissingMarkdown { .. })
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opt = |id: &Option<String>| id.as_deref().map(short_id).unwrap_or("none").to_string();
+        match self {
+            Issue::MissingThread { thread } => {
+                write!(f, "index lists thread {} but its file is missing", short_id(thread))
+            }
+            Issue::ActiveThreadMissing { thread } => {
+                write!(f, "active thread {} does not exist", short_id(thread))
+            }
+            Issue::ActiveThreadUnlisted { thread } => {
+                write!(f, "active thread {} is not listed in the index", short_id(thread))
+            }
+            Issue::CurrentMessageMissing { message } => {
+                write!(f, "current message {} does not exist", short_id(message))
+            }
+            Issue::MissingRoot { thread, message } => write!(
+                f,
+                "[thread {}] root message {} is missing",
+                short_id(thread),
+                short_id(message)
+            ),
+            Issue::DanglingChild { thread, parent, child } => write!(
+                f,
+                "[thread {}] message {} lists missing child {}",
+                short_id(thread),
+                short_id(parent),
+                short_id(child)
+            ),
+            Issue::ParentMismatch { thread, message, expected, found } => write!(
+                f,
+                "[thread {}] message {} has parent {} but is listed under {}",
+                short_id(thread),
+                short_id(message),
+                opt(found),
+                opt(expected)
+            ),
+            Issue::BranchNotInChildren { thread, parent, child } => write!(
+                f,
+                "[thread {}] message {} has branch member {} missing from its children",
+                short_id(thread),
+                short_id(parent),
+                short_id(child)
+            ),
+            Issue::MissingMarkdown { thread, message, path } => write!(
+                f,
+                "[thread {}] message {} links missing markdown file {}",
+                short_id(thread),
+                short_id(message),
+                path
+            ),
+            Issue::DanglingMark { thread: Some(thread), name, message } => write!(
+                f,
+                "[thread {}] mark '{}' points at missing message {}",
+                short_id(thread),
+                name,
+                short_id(message)
+            ),
+            Issue::DanglingMark { thread: None, name, message } => {
+                write!(f, "global mark '{}' points at missing message {}", name, short_id(message))
+            }
+            Issue::DanglingCursor { thread, message } => write!(
+                f,
+                "[thread {}] cursor points at missing message {}",
+                short_id(thread),
+                short_id(message)
+            ),
+        }
+    }
+}
+
+impl FurStore {
+    /// Walk every thread in the index and report every inconsistency.
+    pub fn check(&self) -> Result<Vec<Issue>, StoreError> {
+        let index = self.load_index()?;
+        let mut issues = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+
+        for tid in &index.threads {
+            let thread = match self.load_thread(tid) {
+                Ok(t) => t,
+                Err(StoreError::ThreadNotFound(_)) => {
+                    issues.push(Issue::MissingThread { thread: tid.clone() });
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            for (name, mid) in &thread.marks {
+                if !self.message_exists(mid) {
+                    issues.push(Issue::DanglingMark {
+                        thread: Some(tid.clone()),
+                        name: name.clone(),
+                        message: mid.clone(),
+                    });
+                }
+            }
+
+            if let Some(ref cursor) = thread.cursor {
+                if !self.message_exists(cursor) {
+                    issues.push(Issue::DanglingCursor { thread: tid.clone(), message: cursor.clone() });
+                }
+            }
+
+            // (message, where it is listed)
+            let mut to_visit: Vec<(String, Option<String>)> =
+                thread.messages.iter().rev().map(|m| (m.clone(), None)).collect();
+
+            while let Some((mid, listed_under)) = to_visit.pop() {
+                let msg = match self.load_message(&mid) {
+                    Ok(m) => m,
+                    Err(StoreError::MessageNotFound(_)) => {
+                        issues.push(match listed_under {
+                            None => Issue::MissingRoot { thread: tid.clone(), message: mid },
+                            Some(parent) => Issue::DanglingChild {
+                                thread: tid.clone(),
+                                parent,
+                                child: mid,
+                            },
+                        });
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                if msg.parent != listed_under {
+                    issues.push(Issue::ParentMismatch {
+                        thread: tid.clone(),
+                        message: mid.clone(),
+                        expected: listed_under.clone(),
+                        found: msg.parent.clone(),
+                    });
+                }
+
+                // Shared messages (old forks) are only checked once
+                if !seen.insert(mid.clone()) {
+                    continue;
+                }
+
+                for child in msg.branches.iter().flatten() {
+                    if !msg.children.contains(child) {
+                        issues.push(Issue::BranchNotInChildren {
+                            thread: tid.clone(),
+                            parent: mid.clone(),
+                            child: child.clone(),
+                        });
+                    }
+                }
+
+                if let Some(ref md) = msg.markdown {
+                    if !self.project_path(md).exists() {
+                        issues.push(Issue::MissingMarkdown {
+                            thread: tid.clone(),
+                            message: mid.clone(),
+                            path: md.clone(),
+                        });
+                    }
+                }
+
+                let mut kids = msg.child_ids();
+                kids.reverse();
+                to_visit.extend(kids.into_iter().map(|c| (c, Some(mid.clone()))));
+            }
+        }
+
+        for (name, mid) in &index.marks {
+            if !self.message_exists(mid) {
+                issues.push(Issue::DanglingMark { thread: None, name: name.clone(), message: mid.clone() });
+            }
+        }
+
+        if let Some(ref active) = index.active_thread {
+            // A listed thread without a file is already a `MissingThread`
+            if !index.threads.contains(active) {
+                issues.push(if self.thread_path(active).exists() {
+                    Issue::ActiveThreadUnlisted { thread: active.clone() }
+                } else {
+                    Issue::ActiveThreadMissing { thread: active.clone() }
+                });
+            }
+        }
+        if let Some(ref current) = index.current_message {
+            if !self.message_exists(current) {
+                issues.push(Issue::CurrentMessageMissing { message: current.clone() });
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Repair every fixable issue. Returns how many were repaired.
+    /// Callers should hold the store lock.
+    pub fn repair(&self, issues: &[Issue]) -> Result<usize, StoreError> {
+        let mut fixed = 0;
+
+        for issue in issues.iter().filter(|i| i.is_fixable()) {
+            match issue {
+                Issue::MissingThread { thread } => {
+                    let mut index = self.load_index()?;
+                    index.threads.retain(|t| t != thread);
+                    if index.active_thread.as_ref() == Some(thread) {
+                        self.replace_active_thread(&mut index)?;
+                    }
+                    self.save_index(&index)?;
+                }
+                Issue::ActiveThreadMissing { .. } => {
+                    let mut index = self.load_index()?;
+                    self.replace_active_thread(&mut index)?;
+                    self.save_index(&index)?;
+                }
+                Issue::ActiveThreadUnlisted { thread } => {
+                    let mut index = self.load_index()?;
+                    if !index.threads.contains(thread) {
+                        index.threads.push(thread.clone());
+                        self.save_index(&index)?;
+                    }
+                }
+                Issue::CurrentMessageMissing { message } => {
+                    let mut index = self.load_index()?;
+                    if index.current_message.as_ref() == Some(message) {
+                        index.current_message = None;
+                        self.save_index(&index)?;
+                    }
+                }
+                Issue::MissingRoot { thread, message } => {
+                    let mut t = self.load_thread(thread)?;
+                    t.messages.retain(|m| m != message);
+                    self.save_thread(&t)?;
+                }
+                Issue::DanglingChild { parent, child, .. } => {
+                    let mut p = self.load_message(parent)?;
+                    p.children.retain(|c| c != child);
+                    for group in p.branches.iter_mut() {
+                        group.retain(|c| c != child);
+                    }
+                    p.branches.retain(|g| !g.is_empty());
+                    self.save_message(&p)?;
+                }
+                Issue::ParentMismatch { message, expected, .. } => {
+                    let mut m = self.load_message(message)?;
+                    m.parent = expected.clone();
+                    self.save_message(&m)?;
+                }
+                Issue::BranchNotInChildren { parent, child, .. } => {
+                    let mut p = self.load_message(parent)?;
+                    if !p.children.contains(child) {
+                        p.children.push(child.clone());
+                    }
+                    self.save_message(&p)?;
+                }
+                Issue::DanglingMark { thread: Some(thread), name, .. } => {
+                    let mut t = self.load_thread(thread)?;
+                    t.marks.remove(name);
+                    self.save_thread(&t)?;
+                }
+                Issue::DanglingMark { thread: None, name, .. } => {
+                    let mut index = self.load_index()?;
+                    index.marks.remove(name);
+                    self.save_index(&index)?;
+                }
+                Issue::DanglingCursor { thread, .. } => {
+                    let mut t = self.load_thread(thread)?;
+                    t.cursor = None;
+                    self.save_thread(&t)?;
+                }
+                Issue::MissingMarkdown { .. } => continue,
+            }
+            fixed += 1;
+        }
+
+        Ok(fixed)
+    }
+
+    /// Switch away from a missing active thread to the last one that exists.
+    fn replace_active_thread(&self, index: &mut Index) -> Result<(), StoreError> {
+        index.active_thread = None;
+        index.current_message = None;
+        match index.threads.iter().rev().find(|t| self.thread_path(t).exists()).cloned() {
+            Some(tid) => self.switch_thread(index, &tid),
+            None => Ok(()),
+        }
+    }
+}
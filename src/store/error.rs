@@ -0,0 +1,95 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong while reading or writing `.fur/`.
+#[derive(Debug)]
+pub enum StoreError {
+    /// No `.fur/` (or no `index.json` inside it)
+    NotInitialized(PathBuf),
+    Io { path: PathBuf, source: io::Error },
+    Json { path: PathBuf, source: serde_json::Error },
+    /// Schema version this build has no migration path from
+    UnsupportedSchema(String),
+    /// Another process holds the `.fur/` lock
+    Locked(PathBuf),
+    NoActiveThread,
+    ThreadNotFound(String),
+    MessageNotFound(String),
+    /// An id prefix that matches more than one thing; one line per match
+    AmbiguousId { prefix: String, candidates: Vec<String> },
+    /// `fur rm` without `--recursive` on a message with replies
+    HasChildren { id: String, count: usize },
+    NotInTrash(String),
+    /// A move that would put a message under its own descendant
+    WouldCycle { id: String, parent: String },
+    /// Any other request that doesn't make sense for the current tree
+    Invalid(String),
+}
+
+impl StoreError {
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        StoreError::Io { path: path.into(), source }
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::NotInitialized(path) => {
+                write!(f, "🚨 {} not found. Run `fur new` first.", path.display())
+            }
+            StoreError::Io { path, source } => {
+                write!(f, "❌ Cannot access {}: {}", path.display(), source)
+            }
+            StoreError::Json { path, source } => {
+                write!(f, "❌ Cannot parse {}: {}", path.display(), source)
+            }
+            StoreError::UnsupportedSchema(version) => write!(
+                f,
+                "❌ This store uses schema {}, which this version of fur can't read. Upgrade fur.",
+                version
+            ),
+            StoreError::Locked(path) => write!(
+                f,
+                "🔒 Another `fur` process is writing to this store ({} is held). Try again once it finishes.",
+                path.display()
+            ),
+            StoreError::NoActiveThread => write!(f, "⚠️ No active thread."),
+            StoreError::ThreadNotFound(id) => write!(f, "❌ Thread not found: {}", id),
+            StoreError::MessageNotFound(id) => write!(f, "❌ Message not found: {}", id),
+            StoreError::HasChildren { id, count } => write!(
+                f,
+                "❌ Message {} has {} repl{}. Use --recursive to remove the whole subtree.",
+                super::short_id(id),
+                count,
+                if *count == 1 { "y" } else { "ies" }
+            ),
+            StoreError::WouldCycle { id, parent } => write!(
+                f,
+                "❌ Can't move {} under {}: that message is inside the subtree being moved.",
+                super::short_id(id),
+                super::short_id(parent)
+            ),
+            StoreError::Invalid(reason) => write!(f, "❌ {}", reason),
+            StoreError::NotInTrash(id) => write!(f, "❌ Nothing in the trash matches {}", id),
+            StoreError::AmbiguousId { prefix, candidates } => {
+                write!(f, "❌ '{}' is ambiguous, it matches:", prefix)?;
+                for line in candidates {
+                    write!(f, "\n   {}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Io { source, .. } => Some(source),
+            StoreError::Json { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
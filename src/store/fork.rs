@@ -0,0 +1,101 @@
+//! Forking: copying messages into a new thread so the copies can diverge.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::{FurStore, StoreError, StoredThread};
+
+/// Old id → new id for every message a fork copied.
+pub type CopyMap = HashMap<String, String>;
+
+impl FurStore {
+    /// Copy a message and all of its descendants under fresh ids, hanging
+    /// the copy under `new_parent` (or nowhere, for a root). Each copy
+    /// records the message it came from in `copy_of`. Returns the new id of
+    /// the subtree root; `map` collects every old → new id.
+    pub fn copy_subtree(
+        &self,
+        msg_id: &str,
+        new_parent: Option<String>,
+        map: &mut CopyMap,
+    ) -> Result<String, StoreError> {
+        let ids: Vec<String> =
+            self.subtree_ids(msg_id).into_iter().filter(|m| self.message_exists(m)).collect();
+        for old in &ids {
+            map.insert(old.clone(), Uuid::new_v4().to_string());
+        }
+
+        for old in &ids {
+            let mut msg = self.load_message(old)?;
+            msg.parent = if old == msg_id {
+                new_parent.clone()
+            } else {
+                msg.parent.as_ref().and_then(|p| map.get(p)).cloned()
+            };
+            msg.children = msg.children.iter().filter_map(|c| map.get(c)).cloned().collect();
+            msg.branches = msg
+                .branches
+                .iter()
+                .map(|g| g.iter().filter_map(|c| map.get(c)).cloned().collect::<Vec<_>>())
+                .filter(|g| !g.is_empty())
+                .collect();
+            msg.copy_of = Some(old.clone());
+            msg.id = map[old].clone();
+            self.save_message(&msg)?;
+        }
+
+        Ok(map[msg_id].clone())
+    }
+
+    /// Create a new thread holding a private copy of every message in
+    /// `thread_id`. The new thread is saved but not added to the index.
+    pub fn fork_thread(&self, thread_id: &str, title: &str) -> Result<(StoredThread, CopyMap), StoreError> {
+        let source = self.load_thread(thread_id)?;
+        let mut fork = StoredThread::new(title);
+        fork.forked_from = Some(thread_id.to_string());
+        fork.tags = source.tags.clone();
+
+        let mut map = CopyMap::new();
+        for root in source.messages.iter().filter(|m| self.message_exists(m)) {
+            let copy = self.copy_subtree(root, None, &mut map)?;
+            fork.messages.push(copy);
+        }
+        self.save_thread(&fork)?;
+        Ok((fork, map))
+    }
+
+    /// Create a new thread whose history is a copy of the root-to-`msg_id`
+    /// lineage of `thread_id`, without the other replies along the way.
+    /// The new thread is saved but not added to the index.
+    pub fn fork_lineage(
+        &self,
+        thread_id: &str,
+        msg_id: &str,
+        title: &str,
+    ) -> Result<(StoredThread, CopyMap), StoreError> {
+        let source = self.load_thread(thread_id)?;
+        let mut fork = StoredThread::new(title);
+        fork.forked_from = Some(thread_id.to_string());
+        fork.forked_at = Some(msg_id.to_string());
+        fork.tags = source.tags.clone();
+
+        let lineage = self.lineage(msg_id)?;
+        let map: CopyMap = lineage.iter().map(|old| (old.clone(), Uuid::new_v4().to_string())).collect();
+
+        let mut parent: Option<String> = None;
+        for (i, old) in lineage.iter().enumerate() {
+            let mut msg = self.load_message(old)?;
+            msg.id = map[old].clone();
+            msg.parent = parent.clone();
+            msg.children = lineage.get(i + 1).map(|next| vec![map[next].clone()]).unwrap_or_default();
+            msg.branches = vec![];
+            msg.copy_of = Some(old.clone());
+            self.save_message(&msg)?;
+            parent = Some(msg.id);
+        }
+
+        fork.messages = vec![map[&lineage[0]].clone()];
+        self.save_thread(&fork)?;
+        Ok((fork, map))
+    }
+}
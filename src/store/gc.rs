@@ -0,0 +1,78 @@
+//! Garbage collection of unreachable messages and ephemeral leftovers.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::{json_files, FurStore, StoreError};
+
+/// What a collection found (and removed, unless it was a dry run).
+#[derive(Debug, Default)]
+pub struct GcReport {
+    /// Unreachable messages old enough to collect
+    pub collected: Vec<String>,
+    /// Unreachable messages still inside the grace period
+    pub kept_recent: Vec<String>,
+    /// Leftover ephemeral thread files in `tmp/`
+    pub stale_tmp: Vec<PathBuf>,
+    pub dry_run: bool,
+}
+
+impl FurStore {
+    /// Every message id reachable from a thread listed in the index.
+    pub fn reachable_messages(&self) -> Result<HashSet<String>, StoreError> {
+        let index = self.load_index()?;
+        let mut reachable = HashSet::new();
+        for thread in self.load_threads(&index) {
+            reachable.extend(self.load_tree(&thread).into_keys());
+        }
+        Ok(reachable)
+    }
+
+    /// Collect messages no thread can reach, plus stale `tmp/` files.
+    /// Anything modified within `grace` is left alone. Callers should hold
+    /// the store lock.
+    pub fn gc(&self, grace: Duration, dry_run: bool) -> Result<GcReport, StoreError> {
+        let reachable = self.reachable_messages()?;
+        let cutoff = SystemTime::now().checked_sub(grace).unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut report = GcReport { dry_run, ..GcReport::default() };
+
+        for path in json_files(&self.messages_dir())? {
+            let Some(id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if reachable.contains(&id) {
+                continue;
+            }
+            if modified(&path)? > cutoff {
+                report.kept_recent.push(id);
+            } else {
+                report.collected.push(id);
+            }
+        }
+
+        for path in json_files(&self.tmp_dir())? {
+            if modified(&path)? <= cutoff {
+                report.stale_tmp.push(path);
+            }
+        }
+
+        if !dry_run {
+            for id in &report.collected {
+                self.delete_message_file(id)?;
+            }
+            for path in &report.stale_tmp {
+                fs::remove_file(path).map_err(|e| StoreError::io(path, e))?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+fn modified(path: &Path) -> Result<SystemTime, StoreError> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| StoreError::io(path, e))
+}
@@ -0,0 +1,254 @@
+//! Moving messages in and out of a thread's tree.
+
+use serde::{Deserialize, Serialize};
+
+use super::{short_id, FurStore, Index, OpenBranch, StoreError, StoredThread};
+
+/// Where a message hangs in a thread: under a parent, or at the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slot {
+    pub parent: Option<String>,
+    /// Index in the parent's `children` (or the thread's root list)
+    pub position: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<BranchSlot>,
+}
+
+/// Position inside one of the parent's `branches` groups.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchSlot {
+    pub group: usize,
+    pub index: usize,
+    /// The message was the group's only member (the group was dropped)
+    pub alone: bool,
+}
+
+/// Where `fur mv` put a subtree.
+#[derive(Debug, Clone)]
+pub struct MoveReport {
+    pub from_thread: String,
+    pub to_thread: String,
+    pub from: Slot,
+    pub to: Slot,
+    /// The moved message plus its descendants
+    pub moved: usize,
+}
+
+impl Slot {
+    /// The end of `parent`'s children (or of the root list), outside any branch.
+    pub fn append(parent: Option<String>) -> Self {
+        Slot { parent, position: usize::MAX, branch: None }
+    }
+}
+
+impl FurStore {
+    /// The thread whose tree contains `msg_id`, preferring the active one.
+    pub fn thread_containing(&self, index: &Index, msg_id: &str) -> Option<StoredThread> {
+        let active = index.active_thread.iter();
+        let others = index.threads.iter().filter(|t| index.active_thread.as_ref() != Some(*t));
+        active
+            .chain(others)
+            .filter_map(|tid| self.load_thread(tid).ok())
+            .find(|t| self.load_tree(t).contains_key(msg_id))
+    }
+
+    /// Move a message and its subtree under `new_parent`, or to the end of
+    /// `thread`'s roots when no parent is given. A parent that already has
+    /// branch groups gets the message as a new group of its own, so the
+    /// renderers show it. Callers should hold the store lock.
+    pub fn move_message(
+        &self,
+        msg_id: &str,
+        new_parent: Option<&str>,
+        thread: Option<&str>,
+    ) -> Result<MoveReport, StoreError> {
+        let mut index = self.load_index()?;
+        let mut from_thread = self
+            .thread_containing(&index, msg_id)
+            .ok_or_else(|| StoreError::MessageNotFound(msg_id.to_string()))?;
+        let subtree = self.subtree_ids(msg_id);
+
+        let to = match new_parent {
+            Some(parent_id) => {
+                if subtree.iter().any(|m| m == parent_id) {
+                    return Err(StoreError::WouldCycle {
+                        id: msg_id.to_string(),
+                        parent: parent_id.to_string(),
+                    });
+                }
+                let parent = self.load_message(parent_id)?;
+                let mut slot = Slot::append(Some(parent_id.to_string()));
+                if !parent.branches.is_empty() {
+                    slot.branch = Some(BranchSlot { group: usize::MAX, index: 0, alone: true });
+                }
+                slot
+            }
+            None => Slot::append(None),
+        };
+
+        // Which thread the message ends up in
+        let to_thread_id = match (new_parent, thread) {
+            (Some(parent_id), requested) => {
+                let owner = self
+                    .thread_containing(&index, parent_id)
+                    .ok_or_else(|| StoreError::MessageNotFound(parent_id.to_string()))?;
+                if let Some(tid) = requested {
+                    if tid != owner.id {
+                        return Err(StoreError::Invalid(format!(
+                            "Message {} is not in thread {}",
+                            short_id(parent_id),
+                            short_id(tid)
+                        )));
+                    }
+                }
+                owner.id
+            }
+            (None, Some(tid)) => tid.to_string(),
+            (None, None) => from_thread.id.clone(),
+        };
+
+        let from = self.detach(&mut from_thread, msg_id)?;
+        let mut to_thread = if to_thread_id == from_thread.id {
+            from_thread.clone()
+        } else {
+            self.load_thread(&to_thread_id)?
+        };
+        self.attach(&mut to_thread, msg_id, &to)?;
+
+        // Don't leave the cursor inside a subtree that left the active thread
+        let moved_away = to_thread.id != from_thread.id
+            && index.active_thread.as_deref() == Some(from_thread.id.as_str());
+        if moved_away && index.current_message.as_ref().is_some_and(|c| subtree.contains(c)) {
+            index.current_message = from.parent.clone();
+            self.save_index(&index)?;
+        }
+
+        Ok(MoveReport {
+            from_thread: from_thread.id,
+            to_thread: to_thread.id,
+            from,
+            to,
+            moved: subtree.len(),
+        })
+    }
+
+    /// Ids from the root down to `msg_id`, following `parent` links.
+    pub fn lineage(&self, msg_id: &str) -> Result<Vec<String>, StoreError> {
+        let mut out = vec![msg_id.to_string()];
+        let mut msg = self.load_message(msg_id)?;
+        while let Some(parent) = msg.parent.take() {
+            if out.contains(&parent) {
+                break;
+            }
+            msg = self.load_message(&parent)?;
+            out.push(parent);
+        }
+        out.reverse();
+        Ok(out)
+    }
+
+    /// Add `child_id` under `parent_id` as a member of branch group `group`,
+    /// or of a new group when `None`. Returns the group's index. Only the
+    /// parent is saved; the child's own file is left to the caller.
+    pub fn add_to_branch(&self, parent_id: &str, group: Option<usize>, child_id: &str) -> Result<usize, StoreError> {
+        let mut parent = self.load_message(parent_id)?;
+        let group = match group {
+            Some(g) if g < parent.branches.len() => g,
+            Some(g) => {
+                return Err(StoreError::Invalid(format!(
+                    "Message {} has no branch {} ({} branch group(s)).",
+                    short_id(parent_id),
+                    g + 1,
+                    parent.branches.len()
+                )))
+            }
+            None => {
+                parent.branches.push(vec![]);
+                parent.branches.len() - 1
+            }
+        };
+        parent.branches[group].push(child_id.to_string());
+        if !parent.children.iter().any(|c| c == child_id) {
+            parent.children.push(child_id.to_string());
+        }
+        self.save_message(&parent)?;
+        Ok(group)
+    }
+
+    /// `index.open_branch`, if the cursor is still on its parent or inside
+    /// its group. Jumping anywhere else closes it.
+    pub fn open_branch(&self, index: &Index) -> Option<OpenBranch> {
+        let open = index.open_branch.clone()?;
+        let current = index.current_message.as_deref()?;
+        if current == open.parent {
+            return Some(open);
+        }
+        let parent = self.load_message(&open.parent).ok()?;
+        let members = parent.branches.get(open.group?)?;
+        members.iter().any(|m| m == current).then_some(open)
+    }
+
+    /// Unlink a message from its parent (or from the thread's roots) and
+    /// return where it was. The message's own file is left untouched.
+    /// Saves every file it modifies.
+    pub fn detach(&self, thread: &mut StoredThread, msg_id: &str) -> Result<Slot, StoreError> {
+        let msg = self.load_message(msg_id)?;
+
+        let Some(parent_id) = msg.parent else {
+            let position = thread
+                .messages
+                .iter()
+                .position(|m| m == msg_id)
+                .ok_or_else(|| StoreError::MessageNotFound(msg_id.to_string()))?;
+            thread.messages.remove(position);
+            self.save_thread(thread)?;
+            return Ok(Slot { parent: None, position, branch: None });
+        };
+
+        let mut parent = self.load_message(&parent_id)?;
+        let position = parent.children.iter().position(|c| c == msg_id).unwrap_or(parent.children.len());
+        parent.children.retain(|c| c != msg_id);
+
+        let mut branch = None;
+        for (group, members) in parent.branches.iter_mut().enumerate() {
+            if let Some(index) = members.iter().position(|c| c == msg_id) {
+                members.remove(index);
+                branch = Some(BranchSlot { group, index, alone: members.is_empty() });
+                break;
+            }
+        }
+        parent.branches.retain(|g| !g.is_empty());
+        self.save_message(&parent)?;
+
+        Ok(Slot { parent: Some(parent_id), position, branch })
+    }
+
+    /// Link a message into `slot`, setting its `parent`. Positions past the
+    /// end append. Saves every file it modifies.
+    pub fn attach(&self, thread: &mut StoredThread, msg_id: &str, slot: &Slot) -> Result<(), StoreError> {
+        let mut msg = self.load_message(msg_id)?;
+        msg.parent = slot.parent.clone();
+        self.save_message(&msg)?;
+
+        let Some(ref parent_id) = slot.parent else {
+            let at = slot.position.min(thread.messages.len());
+            thread.messages.insert(at, msg_id.to_string());
+            return self.save_thread(thread);
+        };
+
+        let mut parent = self.load_message(parent_id)?;
+        let at = slot.position.min(parent.children.len());
+        parent.children.insert(at, msg_id.to_string());
+
+        if let Some(ref b) = slot.branch {
+            let group = b.group.min(parent.branches.len());
+            if b.alone || group == parent.branches.len() {
+                parent.branches.insert(group, vec![msg_id.to_string()]);
+            } else {
+                let members = &mut parent.branches[group];
+                members.insert(b.index.min(members.len()), msg_id.to_string());
+            }
+        }
+        self.save_message(&parent)
+    }
+}
@@ -0,0 +1,322 @@
+//! `.fur/ops/`: the operation journal behind `fur undo` / `fur redo`.
+//!
+//! While an operation is open ([`FurStore::begin_op`]), every store file
+//! about to be written or removed has its previous content captured.
+//! [`FurStore::commit_op`] then saves one [`Operation`] holding each touched
+//! file's content before and after, so it can be put back exactly.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{json_files, read_json, remove_if_exists, write_atomic, write_json, FurStore, Index, StoreError, StoredThread};
+
+/// How many operations are kept for undo.
+pub const OP_HISTORY: usize = 100;
+
+/// One undoable command and the files it changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub seq: u64,
+    /// Command name, e.g. `jot` or `thread rename`
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    pub timestamp: String,
+    /// Reverted by `fur undo` (and waiting for `fur redo`)
+    #[serde(default)]
+    pub undone: bool,
+    pub files: Vec<FileChange>,
+}
+
+/// A file's content around an operation; `None` means it didn't exist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Relative to the `.fur/` directory
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// An operation in progress (kept in memory on the store handle).
+#[derive(Debug, Default)]
+pub(crate) struct Journal {
+    name: String,
+    /// Path relative to the store, and its content when first touched
+    before: Vec<(String, Option<String>)>,
+}
+
+impl FurStore {
+    pub fn ops_dir(&self) -> PathBuf {
+        self.root().join("ops")
+    }
+
+    fn op_path(&self, seq: u64) -> PathBuf {
+        self.ops_dir().join(format!("{:06}.json", seq))
+    }
+
+    /// Start recording an undoable operation. Callers should hold the store lock.
+    pub fn begin_op(&self, name: &str) {
+        *self.journal.lock().unwrap() = Some(Journal { name: name.to_string(), before: vec![] });
+    }
+
+    /// Save the open operation (if it changed anything) and drop the redo
+    /// history. Returns its sequence number.
+    pub fn commit_op(&self, detail: impl Into<String>) -> Result<Option<u64>, StoreError> {
+        let Some(journal) = self.journal.lock().unwrap().take() else {
+            return Ok(None);
+        };
+
+        let mut files = Vec::new();
+        for (path, before) in journal.before {
+            let after = read_optional(&self.root().join(&path))?;
+            if after != before {
+                files.push(FileChange { path, before, after });
+            }
+        }
+        if files.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ops = self.load_ops()?;
+        for op in ops.iter().filter(|o| o.undone) {
+            remove_if_exists(&self.op_path(op.seq))?;
+        }
+        ops.retain(|o| !o.undone);
+        while ops.len() >= OP_HISTORY {
+            remove_if_exists(&self.op_path(ops.remove(0).seq))?;
+        }
+
+        let op = Operation {
+            seq: ops.last().map(|o| o.seq + 1).unwrap_or(1),
+            name: journal.name,
+            detail: detail.into(),
+            timestamp: Utc::now().to_rfc3339(),
+            undone: false,
+            files,
+        };
+        fs::create_dir_all(self.ops_dir()).map_err(|e| StoreError::io(self.ops_dir(), e))?;
+        write_json(&self.op_path(op.seq), &op)?;
+        Ok(Some(op.seq))
+    }
+
+    /// Capture `path`'s current content if an operation is open and this
+    /// is the first time it is touched.
+    pub(crate) fn journal_touch(&self, path: &Path) -> Result<(), StoreError> {
+        let mut guard = self.journal.lock().unwrap();
+        let Some(journal) = guard.as_mut() else {
+            return Ok(());
+        };
+        let rel = path.strip_prefix(self.root()).unwrap_or(path).to_string_lossy().to_string();
+        if !journal.before.iter().any(|(p, _)| *p == rel) {
+            journal.before.push((rel, read_optional(path)?));
+        }
+        Ok(())
+    }
+
+    /// `journal_touch` every JSON file directly inside `dir`.
+    pub(crate) fn journal_touch_dir(&self, dir: &Path) -> Result<(), StoreError> {
+        for path in json_files(dir)? {
+            self.journal_touch(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Every recorded operation, oldest first.
+    pub fn load_ops(&self) -> Result<Vec<Operation>, StoreError> {
+        let mut ops: Vec<Operation> =
+            json_files(&self.ops_dir())?.iter().map(|p| read_json(p)).collect::<Result<_, _>>()?;
+        ops.sort_by_key(|o| o.seq);
+        Ok(ops)
+    }
+
+    /// Revert the newest operation not yet undone. Files changed since are
+    /// a conflict unless `force`, except `index.json` (only the fields the
+    /// operation changed are put back) and thread cursors (kept as they
+    /// are). Callers should hold the store lock.
+    pub fn undo(&self, force: bool) -> Result<Operation, StoreError> {
+        let mut op = self
+            .load_ops()?
+            .into_iter()
+            .rev()
+            .find(|o| !o.undone)
+            .ok_or_else(|| StoreError::Invalid("Nothing to undo.".to_string()))?;
+        self.apply_op(&op, true, force)?;
+        op.undone = true;
+        write_json(&self.op_path(op.seq), &op)?;
+        Ok(op)
+    }
+
+    /// Re-apply the oldest undone operation. Callers should hold the store lock.
+    pub fn redo(&self, force: bool) -> Result<Operation, StoreError> {
+        let mut op = self
+            .load_ops()?
+            .into_iter()
+            .find(|o| o.undone)
+            .ok_or_else(|| StoreError::Invalid("Nothing to redo.".to_string()))?;
+        self.apply_op(&op, false, force)?;
+        op.undone = false;
+        write_json(&self.op_path(op.seq), &op)?;
+        Ok(op)
+    }
+
+    /// Write each file's `before` (undo) or `after` (redo) content.
+    fn apply_op(&self, op: &Operation, undo: bool, force: bool) -> Result<(), StoreError> {
+        let sides = |change: &FileChange| {
+            if undo { (change.after.clone(), change.before.clone()) } else { (change.before.clone(), change.after.clone()) }
+        };
+
+        let mut conflicts = Vec::new();
+        for change in &op.files {
+            let (expected, _) = sides(change);
+            let current = read_optional(&self.root().join(&change.path))?;
+            let same = if change.path == "index.json" {
+                true
+            } else if change.path.starts_with("threads/") {
+                without_cursor(&current) == without_cursor(&expected)
+            } else {
+                current == expected
+            };
+            if !same {
+                conflicts.push(change.path.clone());
+            }
+        }
+        if !conflicts.is_empty() && !force {
+            return Err(StoreError::Invalid(format!(
+                "These files changed after `{}` ran: {}. Use --force to overwrite them.",
+                op.name,
+                conflicts.join(", ")
+            )));
+        }
+
+        let mut index_change = None;
+        let mut kept_cursors = Vec::new();
+        let mut emptied = Vec::new();
+        for change in &op.files {
+            let path = self.root().join(&change.path);
+            let (expected, target) = sides(change);
+            let current = read_optional(&path)?;
+            let untouched = current == expected;
+            match target {
+                Some(content) if change.path == "index.json" && !untouched => index_change = Some((expected, content)),
+                Some(content) if change.path.starts_with("threads/") && !untouched && !force => {
+                    // A thread whose cursor moved since: keep the new cursor
+                    let mut thread: StoredThread = parse(&content, &path)?;
+                    if let Some(Ok(now)) = current.as_deref().map(|c| parse::<StoredThread>(c, &path)) {
+                        kept_cursors.push((thread.id.clone(), thread.cursor.clone()));
+                        thread.cursor = now.cursor;
+                    }
+                    write_json(&path, &thread)?;
+                }
+                // Untouched since the operation (or forced): the exact bytes
+                Some(content) => {
+                    if let Some(dir) = path.parent() {
+                        fs::create_dir_all(dir).map_err(|e| StoreError::io(dir, e))?;
+                    }
+                    write_atomic(&path, content.as_bytes())?;
+                }
+                None => {
+                    remove_if_exists(&path)?;
+                    if change.path.starts_with("trash/") {
+                        emptied.extend(path.parent().map(Path::to_path_buf));
+                    }
+                }
+            }
+        }
+
+        // A trash entry whose files all went back leaves no empty folder
+        for dir in emptied {
+            if fs::read_dir(&dir).is_ok_and(|mut d| d.next().is_none()) {
+                fs::remove_dir(&dir).map_err(|e| StoreError::io(&dir, e))?;
+            }
+        }
+
+        // Cursors kept above must still point at a message
+        for (tid, fallback) in kept_cursors {
+            let mut thread = self.load_thread(&tid)?;
+            if thread.cursor.as_ref().is_some_and(|c| !self.message_exists(c)) {
+                thread.cursor = fallback;
+                write_json(&self.thread_path(&tid), &thread)?;
+            }
+        }
+
+        if let Some((Some(expected), target)) = index_change {
+            let path = self.index_path();
+            let from: Index = parse(&expected, &path)?;
+            let to: Index = parse(&target, &path)?;
+            let mut index = self.load_index()?;
+            self.merge_index(&mut index, &from, &to)?;
+            write_json(&path, &index)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the `from` → `to` difference to the current index, leaving
+    /// everything the operation didn't touch (newer threads, marks, ...).
+    fn merge_index(&self, index: &mut Index, from: &Index, to: &Index) -> Result<(), StoreError> {
+        index.threads.retain(|t| !from.threads.contains(t) || to.threads.contains(t));
+        for (i, t) in to.threads.iter().enumerate() {
+            if !from.threads.contains(t) && !index.threads.contains(t) {
+                index.threads.insert(i.min(index.threads.len()), t.clone());
+            }
+        }
+
+        for name in from.marks.keys().chain(to.marks.keys()) {
+            if from.marks.get(name) != to.marks.get(name) {
+                match to.marks.get(name) {
+                    Some(id) => index.marks.insert(name.clone(), id.clone()),
+                    None => index.marks.remove(name),
+                };
+            }
+        }
+        if from.open_branch != to.open_branch {
+            index.open_branch = to.open_branch.clone();
+        }
+
+        // The position goes back only if we're still where the operation
+        // left us; otherwise it's that thread's saved cursor that moves.
+        if (&from.active_thread, &from.current_message) != (&to.active_thread, &to.current_message) {
+            if index.active_thread == from.active_thread {
+                index.active_thread = to.active_thread.clone();
+                index.current_message = to.current_message.clone();
+            } else if let Some(tid) = to.active_thread.as_ref().filter(|_| to.active_thread == from.active_thread) {
+                if let Ok(mut thread) = self.load_thread(tid) {
+                    if thread.cursor == from.current_message {
+                        thread.cursor = to.current_message.clone();
+                        write_json(&self.thread_path(tid), &thread)?;
+                    }
+                }
+            }
+        }
+        if index.active_thread.as_ref().is_some_and(|t| !index.threads.contains(t)) {
+            index.active_thread = index.threads.last().cloned();
+            index.current_message = None;
+        }
+        Ok(())
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(content: &str, path: &Path) -> Result<T, StoreError> {
+    serde_json::from_str(content).map_err(|e| StoreError::Invalid(format!("{}: {}", path.display(), e)))
+}
+
+/// A thread file's JSON with its `cursor` left out.
+fn without_cursor(content: &Option<String>) -> Option<serde_json::Value> {
+    let mut value: serde_json::Value = serde_json::from_str(content.as_deref()?).ok()?;
+    if let Some(map) = value.as_object_mut() {
+        map.remove("cursor");
+    }
+    Some(value)
+}
+
+/// File content, or `None` if it doesn't exist.
+fn read_optional(path: &Path) -> Result<Option<String>, StoreError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(StoreError::io(path, e)),
+    }
+}
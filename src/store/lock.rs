@@ -0,0 +1,42 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use super::StoreError;
+
+/// Advisory lock on a `.fur/` directory, released on drop.
+///
+/// Every mutating command holds one of these for its whole run so two
+/// `fur` processes never interleave their writes.
+#[derive(Debug)]
+pub struct StoreLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl StoreLock {
+    /// Try to take the lock without waiting.
+    pub(crate) fn acquire(path: &Path) -> Result<Self, StoreError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .map_err(|e| StoreError::io(path, e))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(StoreLock { file, path: path.to_path_buf() }),
+            Err(TryLockError::WouldBlock) => Err(StoreError::Locked(path.to_path_buf())),
+            Err(TryLockError::Error(e)) => Err(StoreError::io(path, e)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
@@ -0,0 +1,77 @@
+//! Named bookmarks on messages, kept per thread or globally.
+
+use std::collections::HashMap;
+
+use super::{FurStore, Index, StoreError, StoredThread};
+
+/// Where a bookmark lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkScope {
+    /// `marks` of the thread containing the message
+    Thread,
+    /// `marks` of the index, visible from every thread
+    Global,
+}
+
+impl FurStore {
+    /// Point bookmark `name` at `msg_id`, replacing any previous target.
+    /// Returns the thread the mark was stored in (for thread scope).
+    /// Callers should hold the store lock.
+    pub fn set_mark(&self, name: &str, msg_id: &str, scope: MarkScope) -> Result<Option<String>, StoreError> {
+        let mut index = self.load_index()?;
+        match scope {
+            MarkScope::Global => {
+                if !self.message_exists(msg_id) {
+                    return Err(StoreError::MessageNotFound(msg_id.to_string()));
+                }
+                index.marks.insert(name.to_string(), msg_id.to_string());
+                self.save_index(&index)?;
+                Ok(None)
+            }
+            MarkScope::Thread => {
+                let mut thread = self
+                    .thread_containing(&index, msg_id)
+                    .ok_or_else(|| StoreError::MessageNotFound(msg_id.to_string()))?;
+                thread.marks.insert(name.to_string(), msg_id.to_string());
+                self.save_thread(&thread)?;
+                Ok(Some(thread.id))
+            }
+        }
+    }
+
+    /// Delete a bookmark from the active thread (or the index). Returns
+    /// whether it existed. Callers should hold the store lock.
+    pub fn remove_mark(&self, name: &str, scope: MarkScope) -> Result<bool, StoreError> {
+        let mut index = self.load_index()?;
+        match scope {
+            MarkScope::Global => {
+                let existed = index.marks.remove(name).is_some();
+                self.save_index(&index)?;
+                Ok(existed)
+            }
+            MarkScope::Thread => {
+                let mut thread = self.active_thread(&index)?;
+                let existed = thread.marks.remove(name).is_some();
+                self.save_thread(&thread)?;
+                Ok(existed)
+            }
+        }
+    }
+
+    /// Look a bookmark up in `thread` first, then globally.
+    pub fn find_mark(&self, index: &Index, thread: Option<&StoredThread>, name: &str) -> Option<(String, MarkScope)> {
+        thread
+            .and_then(|t| t.marks.get(name))
+            .map(|id| (id.clone(), MarkScope::Thread))
+            .or_else(|| index.marks.get(name).map(|id| (id.clone(), MarkScope::Global)))
+    }
+
+    /// Message id → names of the bookmarks (thread and global) on it.
+    pub fn marks_by_message(&self, index: &Index, thread: &StoredThread) -> HashMap<String, Vec<String>> {
+        let mut out: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, id) in thread.marks.iter().chain(index.marks.iter()) {
+            out.entry(id.clone()).or_default().push(name.clone());
+        }
+        out
+    }
+}
@@ -0,0 +1,132 @@
+//! Grafting one thread's messages into another.
+
+use std::collections::HashMap;
+
+use super::{CopyMap, FurStore, StoreError, StoredMessage};
+
+/// What a merge grafted where.
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// Target message (`None` = thread root) → new branch group grafted there
+    pub grafted: Vec<(Option<String>, Vec<String>)>,
+    /// Messages copied into the target
+    pub copied: usize,
+    /// Source messages skipped because the target already has them
+    pub skipped: usize,
+}
+
+impl FurStore {
+    /// The message's id followed by every id it was copied from.
+    fn provenance(&self, msg: &StoredMessage) -> Vec<String> {
+        let mut chain = vec![msg.id.clone()];
+        let mut next = msg.copy_of.clone();
+        while let Some(id) = next.take() {
+            if chain.contains(&id) {
+                break;
+            }
+            next = self.load_message(&id).ok().and_then(|m| m.copy_of);
+            chain.push(id);
+        }
+        chain
+    }
+
+    /// Copy the messages of `source` that `target` doesn't have yet into
+    /// `target`. A message counts as present when it shares its id or copy
+    /// provenance with a target message (e.g. both sides of a fork).
+    ///
+    /// `from` limits the source to one subtree. New messages whose parent is
+    /// present are grafted under the target's counterpart; the rest go under
+    /// `at` (or become roots when `at` is `None`). Each graft point gets the
+    /// new messages as one new branch group. Callers should hold the lock.
+    pub fn merge_thread(
+        &self,
+        source: &str,
+        target: &str,
+        from: Option<&str>,
+        at: Option<&str>,
+    ) -> Result<MergeReport, StoreError> {
+        let source = self.load_thread(source)?;
+        let mut target = self.load_thread(target)?;
+        if let Some(anchor) = at {
+            if !self.load_tree(&target).contains_key(anchor) {
+                return Err(StoreError::Invalid(format!(
+                    "Message {} is not in thread \"{}\"",
+                    super::short_id(anchor),
+                    target.title
+                )));
+            }
+        }
+
+        if let Some(start) = from {
+            if !self.load_tree(&source).contains_key(start) {
+                return Err(StoreError::Invalid(format!(
+                    "Message {} is not in thread \"{}\"",
+                    super::short_id(start),
+                    source.title
+                )));
+            }
+        }
+
+        // Every provenance id in the target → the target message carrying it
+        let mut present: HashMap<String, String> = HashMap::new();
+        for msg in self.load_tree(&target).values() {
+            for key in self.provenance(msg) {
+                present.entry(key).or_insert_with(|| msg.id.clone());
+            }
+        }
+        let counterpart = |msg: &StoredMessage| {
+            self.provenance(msg).iter().find_map(|k| present.get(k).cloned())
+        };
+
+        let starts = match from {
+            Some(id) => vec![id.to_string()],
+            None => source.messages.clone(),
+        };
+
+        // Walk the source; collect the first new message on each path,
+        // keyed by where it should be grafted.
+        let mut groups: Vec<(Option<String>, Vec<String>)> = Vec::new();
+        let mut report = MergeReport::default();
+        let mut to_visit: Vec<(String, Option<String>)> =
+            starts.into_iter().rev().map(|id| (id, at.map(str::to_string))).collect();
+
+        while let Some((mid, anchor)) = to_visit.pop() {
+            let Ok(msg) = self.load_message(&mid) else { continue };
+            match counterpart(&msg) {
+                Some(existing) => {
+                    report.skipped += 1;
+                    let mut kids = msg.child_ids();
+                    kids.reverse();
+                    to_visit.extend(kids.into_iter().map(|c| (c, Some(existing.clone()))));
+                }
+                None => match groups.iter_mut().find(|(a, _)| *a == anchor) {
+                    Some((_, ids)) => ids.push(mid),
+                    None => groups.push((anchor, vec![mid])),
+                },
+            }
+        }
+
+        let mut map = CopyMap::new();
+        for (anchor, ids) in groups {
+            let mut copies = Vec::new();
+            for id in &ids {
+                copies.push(self.copy_subtree(id, anchor.clone(), &mut map)?);
+            }
+            match anchor {
+                Some(ref parent_id) => {
+                    let mut parent = self.load_message(parent_id)?;
+                    parent.children.extend(copies.iter().cloned());
+                    parent.branches.push(copies.clone());
+                    self.save_message(&parent)?;
+                }
+                None => {
+                    target.messages.extend(copies.iter().cloned());
+                    self.save_thread(&target)?;
+                }
+            }
+            report.grafted.push((anchor, copies));
+        }
+        report.copied = map.len();
+        Ok(report)
+    }
+}
@@ -0,0 +1,224 @@
+//! Versioned schema migrations for `.fur/`.
+//!
+//! Each [`Migration`] upgrades the store by exactly one schema version and
+//! knows how to rewrite every kind of file in it. Steps work on raw JSON
+//! (older shapes may not deserialize into today's structs) and must be
+//! idempotent: `index.json` is bumped last, so an interrupted run simply
+//! starts over.
+
+use std::path::PathBuf;
+use serde_json::{json, Value};
+
+use super::{json_files, read_json, write_json, FurStore, StoreError, SCHEMA_VERSION};
+
+/// Version assumed for stores written before `schema_version` existed.
+pub const BASE_VERSION: &str = "0.1";
+
+/// The kinds of file a migration can touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFile {
+    Index,
+    Thread,
+    Message,
+    Avatars,
+}
+
+/// One step in the schema chain.
+#[derive(Debug)]
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub description: &'static str,
+    pub index: fn(&mut Value),
+    pub thread: fn(&mut Value),
+    pub message: fn(&mut Value),
+    pub avatars: fn(&mut Value),
+}
+
+impl Migration {
+    fn apply(&self, kind: SchemaFile, value: &mut Value) {
+        match kind {
+            SchemaFile::Index => (self.index)(value),
+            SchemaFile::Thread => (self.thread)(value),
+            SchemaFile::Message => (self.message)(value),
+            SchemaFile::Avatars => (self.avatars)(value),
+        }
+    }
+}
+
+/// Every known step, oldest first. The last `to` must equal [`SCHEMA_VERSION`].
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: "0.1",
+        to: "0.2",
+        description: "version bump only (no file changes)",
+        index: unchanged,
+        thread: unchanged,
+        message: unchanged,
+        avatars: unchanged,
+    },
+    Migration {
+        from: "0.2",
+        to: "0.3",
+        description: "normalize message, thread, index and avatar shapes",
+        index: normalize_index,
+        thread: normalize_thread,
+        message: normalize_message,
+        avatars: normalize_avatars,
+    },
+];
+
+/// What a migration run did (or would do, for a dry run).
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub steps: Vec<&'static Migration>,
+    pub changed: Vec<PathBuf>,
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    pub fn is_noop(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl FurStore {
+    /// Schema version recorded in `index.json`.
+    pub fn schema_version(&self) -> Result<String, StoreError> {
+        let index: Value = read_json(&self.index_path())?;
+        Ok(index["schema_version"].as_str().unwrap_or(BASE_VERSION).to_string())
+    }
+
+    /// Steps needed to bring this store up to [`SCHEMA_VERSION`].
+    pub fn pending_migrations(&self) -> Result<Vec<&'static Migration>, StoreError> {
+        let mut version = self.schema_version()?;
+        let mut steps = Vec::new();
+
+        while version != SCHEMA_VERSION {
+            let Some(step) = MIGRATIONS.iter().find(|m| m.from == version) else {
+                return Err(StoreError::UnsupportedSchema(version));
+            };
+            steps.push(step);
+            version = step.to.to_string();
+        }
+        Ok(steps)
+    }
+
+    /// Run every pending step. With `dry_run`, only report what would change.
+    pub fn migrate(&self, dry_run: bool) -> Result<MigrationReport, StoreError> {
+        let steps = self.pending_migrations()?;
+        let mut report = MigrationReport {
+            from: self.schema_version()?,
+            to: SCHEMA_VERSION.to_string(),
+            steps,
+            changed: vec![],
+            dry_run,
+        };
+        if report.is_noop() {
+            return Ok(report);
+        }
+
+        let mut files = self.schema_files()?;
+        // index.json goes last so the version only moves once everything else has
+        files.push((self.index_path(), SchemaFile::Index));
+
+        for (path, kind) in files {
+            let original: Value = read_json(&path)?;
+            let mut value = original.clone();
+            for step in &report.steps {
+                step.apply(kind, &mut value);
+            }
+            if kind == SchemaFile::Index {
+                value["schema_version"] = json!(SCHEMA_VERSION);
+            }
+
+            if value != original {
+                if !dry_run {
+                    write_json(&path, &value)?;
+                }
+                report.changed.push(path);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Threads, messages and avatars (everything but the index).
+    fn schema_files(&self) -> Result<Vec<(PathBuf, SchemaFile)>, StoreError> {
+        let mut files = Vec::new();
+        for (dir, kind) in [
+            (self.threads_dir(), SchemaFile::Thread),
+            (self.messages_dir(), SchemaFile::Message),
+        ] {
+            for path in json_files(&dir)? {
+                files.push((path, kind));
+            }
+        }
+        if self.avatars_path().exists() {
+            files.push((self.avatars_path(), SchemaFile::Avatars));
+        }
+        Ok(files)
+    }
+}
+
+// ------------------ Steps ------------------
+
+fn unchanged(_: &mut Value) {}
+
+/// 0.3: `current_message` is always present (null when unset).
+fn normalize_index(index: &mut Value) {
+    if let Some(obj) = index.as_object_mut() {
+        obj.entry("threads").or_insert_with(|| json!([]));
+        obj.entry("current_message").or_insert(Value::Null);
+    }
+}
+
+/// 0.3: every thread carries `tags` and `messages` arrays (old forks had no tags).
+fn normalize_thread(thread: &mut Value) {
+    if let Some(obj) = thread.as_object_mut() {
+        obj.entry("tags").or_insert_with(|| json!([]));
+        obj.entry("messages").or_insert_with(|| json!([]));
+    }
+}
+
+/// 0.3: `children`/`branches` are always arrays, every branch member is also
+/// listed in `children`, and null optional fields are dropped.
+fn normalize_message(msg: &mut Value) {
+    let Some(obj) = msg.as_object_mut() else { return };
+
+    for key in ["text", "markdown", "attachment", "parent", "name"] {
+        if obj.get(key).is_some_and(|v| v.is_null()) {
+            obj.remove(key);
+        }
+    }
+
+    let mut children: Vec<Value> = obj
+        .get("children")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let branches: Vec<Value> = obj
+        .get("branches")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    for member in branches.iter().filter_map(|b| b.as_array()).flatten() {
+        if !children.contains(member) {
+            children.push(member.clone());
+        }
+    }
+
+    obj.insert("children".to_string(), Value::Array(children));
+    obj.insert("branches".to_string(), Value::Array(branches));
+}
+
+/// 0.3: the `main` pointer always names an avatar that exists.
+fn normalize_avatars(avatars: &mut Value) {
+    let Some(obj) = avatars.as_object_mut() else { return };
+    if let Some(main) = obj.get("main").and_then(|v| v.as_str()).map(|s| s.to_string()) {
+        obj.entry(main).or_insert_with(|| json!("🦊"));
+    }
+}
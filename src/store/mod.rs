@@ -0,0 +1,491 @@
+//! Typed access to the `.fur/` directory.
+//!
+//! Layout:
+//! - `index.json` → [`Index`]
+//! - `threads/<id>.json` → [`StoredThread`]
+//! - `messages/<id>.json` → [`StoredMessage`]
+//! - `avatars.json` → name → emoji map (plus the `main` pointer)
+//! - `tmp/` → ephemeral threads used by `.frs` previews
+//! - `trash/<id>/` → messages removed by `fur rm`, until `fur restore`
+//! - `lock` → advisory lock taken by mutating commands
+//!
+//! Every file is written to a temp sibling and renamed into place, so a
+//! crash never leaves a half-written JSON file behind.
+
+mod doctor;
+mod error;
+mod fork;
+mod gc;
+mod graph;
+mod journal;
+mod lock;
+mod marks;
+mod merge;
+pub mod migrate;
+mod model;
+pub mod navigate;
+mod reflog;
+mod resolve;
+mod revise;
+mod trash;
+
+pub use doctor::Issue;
+pub use error::StoreError;
+pub use fork::CopyMap;
+pub use gc::GcReport;
+pub use graph::{BranchSlot, MoveReport, Slot};
+pub use journal::{FileChange, Operation};
+pub use lock::StoreLock;
+pub use marks::MarkScope;
+pub use merge::MergeReport;
+pub use model::{Index, OpenBranch, Revision, StoredMessage, StoredThread};
+pub use reflog::{Position, ReflogEntry};
+pub use trash::TrashEntry;
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use chrono::Utc;
+
+use journal::Journal;
+
+/// Schema version written by this build.
+pub const SCHEMA_VERSION: &str = "0.3";
+
+/// Environment variable that points commands at a specific store.
+pub const FUR_DIR_ENV: &str = "FUR_DIR";
+
+/// Prefix used for ephemeral (preview) thread ids living in `tmp/`.
+pub const EPHEMERAL_PREFIX: &str = "ephemeral-";
+
+/// First 8 chars of an id, as shown throughout the CLI.
+pub fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// Handle on a `.fur/` directory.
+#[derive(Debug, Clone)]
+pub struct FurStore {
+    root: PathBuf,
+    /// The undoable operation being recorded, if any (see `begin_op`)
+    journal: Arc<Mutex<Option<Journal>>>,
+}
+
+impl FurStore {
+    /// Open an existing store, upgrading its schema first if it is behind.
+    /// Fails if `index.json` is missing.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let store = Self::open_unmigrated(root)?;
+        store.auto_migrate()?;
+        Ok(store)
+    }
+
+    /// Open an existing store as-is, without running migrations.
+    pub fn open_unmigrated(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let store = FurStore { root: root.into(), journal: Default::default() };
+        if !store.index_path().exists() {
+            return Err(StoreError::NotInitialized(store.root));
+        }
+        Ok(store)
+    }
+
+    /// Where commands should look for the store: `explicit` (the
+    /// `--fur-dir` flag), else `$FUR_DIR`, else the nearest `.fur/` above
+    /// the current directory, else `./.fur`.
+    pub fn locate(explicit: Option<&Path>) -> PathBuf {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        if let Some(dir) = explicit {
+            return cwd.join(dir);
+        }
+        if let Some(dir) = std::env::var_os(FUR_DIR_ENV).filter(|d| !d.is_empty()) {
+            return cwd.join(dir);
+        }
+        Self::discover(&cwd).unwrap_or_else(|| cwd.join(".fur"))
+    }
+
+    /// Walk up from `start` to the nearest directory holding an initialized
+    /// `.fur/`, the way git finds `.git/`.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(".fur"))
+            .find(|fur| fur.join("index.json").is_file())
+    }
+
+    /// Create the directory layout and an empty index if needed.
+    pub fn init(root: impl Into<PathBuf>) -> Result<Self, StoreError> {
+        let store = FurStore { root: root.into(), journal: Default::default() };
+        for dir in [store.threads_dir(), store.messages_dir()] {
+            fs::create_dir_all(&dir).map_err(|e| StoreError::io(&dir, e))?;
+        }
+        if !store.index_path().exists() {
+            let index = Index {
+                created_at: Some(Utc::now().to_rfc3339()),
+                schema_version: Some(SCHEMA_VERSION.to_string()),
+                ..Index::default()
+            };
+            store.save_index(&index)?;
+        } else {
+            store.auto_migrate()?;
+        }
+        Ok(store)
+    }
+
+    /// Bring an older store up to [`SCHEMA_VERSION`], noting it on stderr.
+    fn auto_migrate(&self) -> Result<(), StoreError> {
+        if self.pending_migrations()?.is_empty() {
+            return Ok(());
+        }
+        let _lock = self.lock()?;
+        let report = self.migrate(false)?;
+        if !report.is_noop() {
+            eprintln!(
+                "🔧 Upgraded {} from schema {} to {} ({} file(s) rewritten)",
+                self.root.display(),
+                report.from,
+                report.to,
+                report.changed.len()
+            );
+        }
+        Ok(())
+    }
+
+    // ------------------ Paths ------------------
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn index_path(&self) -> PathBuf {
+        self.root.join("index.json")
+    }
+
+    pub fn avatars_path(&self) -> PathBuf {
+        self.root.join("avatars.json")
+    }
+
+    pub fn threads_dir(&self) -> PathBuf {
+        self.root.join("threads")
+    }
+
+    pub fn messages_dir(&self) -> PathBuf {
+        self.root.join("messages")
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.root.join("lock")
+    }
+
+    pub fn tmp_dir(&self) -> PathBuf {
+        self.root.join("tmp")
+    }
+
+    /// Ephemeral ids resolve into `tmp/`, everything else into `threads/`.
+    pub fn thread_path(&self, id: &str) -> PathBuf {
+        if id.starts_with(EPHEMERAL_PREFIX) {
+            self.tmp_dir().join(format!("{}.json", id))
+        } else {
+            self.threads_dir().join(format!("{}.json", id))
+        }
+    }
+
+    pub fn message_path(&self, id: &str) -> PathBuf {
+        self.messages_dir().join(format!("{}.json", id))
+    }
+
+    /// The project directory, i.e. the one containing `.fur/`.
+    pub fn project_dir(&self) -> &Path {
+        match self.root.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        }
+    }
+
+    /// Resolve a path stored in a message (relative to the project).
+    pub fn project_path(&self, path: &str) -> PathBuf {
+        self.project_dir().join(path)
+    }
+
+    /// Turn a path given on the command line (relative to the current
+    /// directory) into one relative to the project, so it resolves the same
+    /// way no matter where `fur` is run from. Paths outside the project are
+    /// kept absolute.
+    pub fn to_project_path(&self, path: &str) -> String {
+        let given = Path::new(path);
+        let abs = match std::env::current_dir() {
+            Ok(cwd) => cwd.join(given),
+            Err(_) => return path.to_string(),
+        };
+        let project = self.project_dir().canonicalize().ok();
+        let abs = abs.canonicalize().unwrap_or(abs);
+        match project.as_deref().and_then(|p| abs.strip_prefix(p).ok()) {
+            Some(rel) => rel.to_string_lossy().to_string(),
+            None if given.is_absolute() => path.to_string(),
+            None => abs.to_string_lossy().to_string(),
+        }
+    }
+
+    // ------------------ Locking ------------------
+
+    /// Take the store-wide write lock. Fails fast with
+    /// [`StoreError::Locked`] if another process holds it.
+    pub fn lock(&self) -> Result<StoreLock, StoreError> {
+        StoreLock::acquire(&self.lock_path())
+    }
+
+    // ------------------ Index ------------------
+
+    pub fn load_index(&self) -> Result<Index, StoreError> {
+        read_json(&self.index_path())
+    }
+
+    pub fn save_index(&self, index: &Index) -> Result<(), StoreError> {
+        self.journal_touch(&self.index_path())?;
+        write_json(&self.index_path(), index)
+    }
+
+    // ------------------ Threads ------------------
+
+    pub fn load_thread(&self, id: &str) -> Result<StoredThread, StoreError> {
+        match read_json(&self.thread_path(id)) {
+            Err(StoreError::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                Err(StoreError::ThreadNotFound(id.to_string()))
+            }
+            other => other,
+        }
+    }
+
+    pub fn save_thread(&self, thread: &StoredThread) -> Result<(), StoreError> {
+        let path = self.thread_path(&thread.id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| StoreError::io(dir, e))?;
+        }
+        self.journal_touch(&path)?;
+        write_json(&path, thread)
+    }
+
+    pub fn delete_thread_file(&self, id: &str) -> Result<(), StoreError> {
+        self.journal_touch(&self.thread_path(id))?;
+        remove_if_exists(&self.thread_path(id))
+    }
+
+    /// The thread `index.active_thread` points at.
+    pub fn active_thread(&self, index: &Index) -> Result<StoredThread, StoreError> {
+        match index.active_thread.as_deref() {
+            Some(id) if !id.is_empty() => self.load_thread(id),
+            _ => Err(StoreError::NoActiveThread),
+        }
+    }
+
+    /// Make `tid` the active thread. The thread being left keeps the current
+    /// message as its cursor, and `tid`'s own cursor becomes the current
+    /// message (dropped if it no longer points into that thread).
+    /// Callers save the index and should hold the store lock.
+    pub fn switch_thread(&self, index: &mut Index, tid: &str) -> Result<(), StoreError> {
+        if index.active_thread.as_deref() == Some(tid) {
+            return Ok(());
+        }
+        if let Some(old) = index.active_thread.as_deref() {
+            if let Ok(mut thread) = self.load_thread(old) {
+                if thread.cursor != index.current_message {
+                    thread.cursor = index.current_message.clone();
+                    self.save_thread(&thread)?;
+                }
+            }
+        }
+
+        let thread = self.load_thread(tid)?;
+        index.active_thread = Some(tid.to_string());
+        index.current_message = thread.cursor.clone().filter(|c| self.load_tree(&thread).contains_key(c));
+        Ok(())
+    }
+
+    /// Every thread listed in the index, in order. Unreadable ones are skipped.
+    pub fn load_threads(&self, index: &Index) -> Vec<StoredThread> {
+        index
+            .threads
+            .iter()
+            .filter_map(|tid| self.load_thread(tid).ok())
+            .collect()
+    }
+
+    pub fn find_thread_by_title(&self, index: &Index, title: &str) -> Option<StoredThread> {
+        self.load_threads(index).into_iter().find(|t| t.title == title)
+    }
+
+    /// Delete a thread, drop it from the index and delete every message
+    /// no other thread still reaches. Returns how many messages went.
+    pub fn delete_thread(&self, id: &str) -> Result<usize, StoreError> {
+        let thread = self.load_thread(id).ok();
+
+        let mut index = self.load_index()?;
+        if index.threads.iter().any(|t| t == id) {
+            index.threads.retain(|t| t != id);
+            if index.active_thread.as_deref() == Some(id) {
+                index.active_thread = None;
+                index.current_message = None;
+                if let Some(next) = index.threads.last().cloned() {
+                    self.switch_thread(&mut index, &next)?;
+                }
+            }
+            self.save_index(&index)?;
+        }
+
+        let mut deleted = 0;
+        if let Some(thread) = thread {
+            let reachable = self.reachable_messages()?;
+            for root in &thread.messages {
+                for mid in self.subtree_ids(root) {
+                    if !reachable.contains(&mid) && self.message_exists(&mid) {
+                        self.delete_message_file(&mid)?;
+                        deleted += 1;
+                    }
+                }
+            }
+        }
+
+        self.delete_thread_file(id)?;
+        Ok(deleted)
+    }
+
+    // ------------------ Messages ------------------
+
+    pub fn message_exists(&self, id: &str) -> bool {
+        self.message_path(id).exists()
+    }
+
+    pub fn load_message(&self, id: &str) -> Result<StoredMessage, StoreError> {
+        match read_json(&self.message_path(id)) {
+            Err(StoreError::Io { source, .. }) if source.kind() == io::ErrorKind::NotFound => {
+                Err(StoreError::MessageNotFound(id.to_string()))
+            }
+            other => other,
+        }
+    }
+
+    pub fn save_message(&self, msg: &StoredMessage) -> Result<(), StoreError> {
+        self.journal_touch(&self.message_path(&msg.id))?;
+        write_json(&self.message_path(&msg.id), msg)
+    }
+
+    pub fn delete_message_file(&self, id: &str) -> Result<(), StoreError> {
+        self.journal_touch(&self.message_path(id))?;
+        remove_if_exists(&self.message_path(id))
+    }
+
+    /// Preload every message reachable from the thread's roots.
+    /// Missing or unreadable files are skipped.
+    pub fn load_tree(&self, thread: &StoredThread) -> HashMap<String, StoredMessage> {
+        let mut id_to_message = HashMap::new();
+        let mut to_visit: Vec<String> = thread.messages.clone();
+
+        while let Some(mid) = to_visit.pop() {
+            if id_to_message.contains_key(&mid) {
+                continue;
+            }
+            if let Ok(msg) = self.load_message(&mid) {
+                to_visit.extend(msg.child_ids());
+                id_to_message.insert(mid, msg);
+            }
+        }
+        id_to_message
+    }
+
+    /// Ids of a message and all of its descendants (depth-first, parent first).
+    pub fn subtree_ids(&self, msg_id: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut to_visit = vec![msg_id.to_string()];
+        while let Some(mid) = to_visit.pop() {
+            if out.contains(&mid) {
+                continue;
+            }
+            if let Ok(msg) = self.load_message(&mid) {
+                let mut kids = msg.child_ids();
+                kids.reverse();
+                to_visit.extend(kids);
+            }
+            out.push(mid);
+        }
+        out
+    }
+
+    // ------------------ Avatars ------------------
+
+    /// `avatars.json`, or an empty object if it doesn't exist yet.
+    pub fn load_avatars(&self) -> Result<Value, StoreError> {
+        let path = self.avatars_path();
+        if !path.exists() {
+            return Ok(json!({}));
+        }
+        read_json(&path)
+    }
+
+    pub fn save_avatars(&self, avatars: &Value) -> Result<(), StoreError> {
+        self.journal_touch(&self.avatars_path())?;
+        write_json(&self.avatars_path(), avatars)
+    }
+}
+
+// ------------------ Helpers ------------------
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, StoreError> {
+    let raw = fs::read_to_string(path).map_err(|e| StoreError::io(path, e))?;
+    serde_json::from_str(&raw).map_err(|source| StoreError::Json { path: path.to_path_buf(), source })
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), StoreError> {
+    let serialized = serde_json::to_string_pretty(value)
+        .map_err(|source| StoreError::Json { path: path.to_path_buf(), source })?;
+    write_atomic(path, serialized.as_bytes())
+}
+
+/// Write to a temp file next to `path`, flush it to disk, then rename it
+/// over `path`. Readers see either the old or the new content, never half.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), StoreError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let written = File::create(&tmp)
+        .and_then(|mut f| {
+            f.write_all(bytes)?;
+            f.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+
+    written.map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        StoreError::io(path, e)
+    })
+}
+
+/// `*.json` files directly inside `dir`, sorted. A missing dir is empty.
+pub(crate) fn json_files(dir: &Path) -> Result<Vec<PathBuf>, StoreError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(StoreError::io(dir, e)),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension().is_some_and(|ext| ext == "json")
+                && !p.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), StoreError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(StoreError::io(path, e)),
+        _ => Ok(()),
+    }
+}
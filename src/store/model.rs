@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use serde_json::{Map, Value};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// `.fur/index.json` → global state shared by every thread.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    #[serde(default)]
+    pub threads: Vec<String>,
+
+    #[serde(default)]
+    pub active_thread: Option<String>,
+
+    #[serde(default)]
+    pub current_message: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+
+    /// Global bookmarks: name → message id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub marks: BTreeMap<String, String>,
+
+    /// Branch group that plain `fur jot`s currently land in (see `fur branch`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_branch: Option<OpenBranch>,
+
+    /// Keys we don't know about are carried through untouched.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A branch group being jotted into.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenBranch {
+    /// The message the group hangs under
+    pub parent: String,
+    /// Index into the parent's `branches`; `None` until the first jot creates it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<usize>,
+}
+
+/// `.fur/threads/<id>.json` → one conversation and its root messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredThread {
+    pub id: String,
+
+    #[serde(default = "untitled")]
+    pub title: String,
+
+    #[serde(default)]
+    pub created_at: String,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Root-level message ids, in order.
+    #[serde(default)]
+    pub messages: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
+
+    /// The message a `fork --at` started from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_at: Option<String>,
+
+    /// Bookmarks in this thread: name → message id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub marks: BTreeMap<String, String>,
+
+    /// Where this thread was left; restored as `current_message` when it
+    /// becomes active again (the active thread's head lives in the index)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+
+    /// Hidden from `fur thread` unless `--all` is given
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub archived: bool,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `.fur/messages/<id>.json` → a single jot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub id: String,
+
+    #[serde(default)]
+    pub avatar: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// Path of a linked markdown file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markdown: Option<String>,
+
+    /// Path of an image or other binary attachment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<String>,
+
+    /// Labels from `#tag` attributes in `.frs` scripts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+
+    /// Every direct child, whether or not it belongs to a branch group.
+    #[serde(default)]
+    pub children: Vec<String>,
+
+    /// Alternative continuations, each a list of child ids.
+    #[serde(default)]
+    pub branches: Vec<Vec<String>>,
+
+    #[serde(default)]
+    pub timestamp: String,
+
+    /// The message this one was copied from by a fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copy_of: Option<String>,
+
+    /// Superseded versions, oldest first (see `fur edit`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<Revision>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A previous version of a message, kept when it is edited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    /// When this version was replaced
+    pub timestamp: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// Contents of the linked markdown file at the time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub markdown_contents: Option<String>,
+}
+
+fn untitled() -> String {
+    "Untitled".to_string()
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+impl StoredThread {
+    /// A fresh, empty thread with a new id.
+    pub fn new(title: &str) -> Self {
+        StoredThread {
+            id: Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            tags: vec![],
+            messages: vec![],
+            forked_from: None,
+            forked_at: None,
+            marks: BTreeMap::new(),
+            cursor: None,
+            archived: false,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl StoredMessage {
+    /// A fresh message with a new id and the current timestamp.
+    pub fn new(avatar: &str, parent: Option<String>) -> Self {
+        StoredMessage {
+            id: Uuid::new_v4().to_string(),
+            avatar: avatar.to_string(),
+            name: None,
+            text: None,
+            markdown: None,
+            attachment: None,
+            tags: vec![],
+            parent,
+            children: vec![],
+            branches: vec![],
+            timestamp: Utc::now().to_rfc3339(),
+            copy_of: None,
+            revisions: vec![],
+            extra: Map::new(),
+        }
+    }
+
+    /// Text to show in listings: the jot text, else the markdown path.
+    pub fn display_text(&self) -> &str {
+        self.text
+            .as_deref()
+            .or(self.markdown.as_deref())
+            .unwrap_or("<no content>")
+    }
+
+    /// First line of the display text, capped at 40 chars.
+    pub fn preview(&self) -> String {
+        self.display_text().lines().next().unwrap_or("").chars().take(40).collect()
+    }
+
+    /// All direct children (plain + branch members), without duplicates.
+    pub fn child_ids(&self) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for cid in self.children.iter().chain(self.branches.iter().flatten()) {
+            if !out.contains(cid) {
+                out.push(cid.clone());
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,78 @@
+//! Walking a thread tree loaded with `FurStore::load_tree`.
+
+use std::collections::HashMap;
+
+use super::{StoreError, StoredMessage, StoredThread};
+
+/// Loaded messages of one thread, by id.
+pub type Tree = HashMap<String, StoredMessage>;
+
+/// Walks backwards from a message to compute its branch path label,
+/// e.g. `[Branch 1.2]`, or `[Root]` outside any branch group.
+pub fn compute_branch_label(msg_id: &str, tree: &Tree) -> String {
+    let labels: Vec<String> = branch_path(msg_id, tree).iter().map(|n| n.to_string()).collect();
+    if labels.is_empty() {
+        "[Root]".to_string()
+    } else {
+        format!("[Branch {}]", labels.join("."))
+    }
+}
+
+/// The 1-based branch group numbers from the root down to `msg_id`.
+pub fn branch_path(msg_id: &str, tree: &Tree) -> Vec<usize> {
+    let mut path = vec![];
+    let mut current_id = msg_id;
+
+    while let Some(msg) = tree.get(current_id) {
+        let Some(ref parent_id) = msg.parent else { break };
+        if let Some(parent) = tree.get(parent_id) {
+            for (b_idx, branch) in parent.branches.iter().enumerate() {
+                if branch.iter().any(|c| c == current_id) {
+                    path.push(b_idx + 1);
+                }
+            }
+        }
+        current_id = parent_id;
+    }
+
+    path.reverse();
+    path
+}
+
+/// The message and its siblings in order: the parent's children, or the
+/// thread's roots for a root message.
+pub fn siblings(msg_id: &str, tree: &Tree, thread: &StoredThread) -> Vec<String> {
+    match tree.get(msg_id).and_then(|m| m.parent.as_ref()).and_then(|p| tree.get(p)) {
+        Some(parent) => parent.child_ids(),
+        None => thread.messages.clone(),
+    }
+}
+
+/// Follow first children from `msg_id` down to a leaf.
+pub fn first_leaf(msg_id: &str, tree: &Tree) -> String {
+    let mut current = msg_id.to_string();
+    while let Some(next) = tree.get(&current).and_then(|m| m.child_ids().into_iter().next()) {
+        current = next;
+    }
+    current
+}
+
+/// Resolve a branch label like `1.2` to the first message, in tree order
+/// from `roots`, whose [`branch_path`] (and so `compute_branch_label`) it is.
+pub fn find_branch(roots: &[String], spec: &str, tree: &Tree) -> Result<String, StoreError> {
+    let wanted = spec
+        .split('.')
+        .map(|part| part.trim().parse::<usize>().ok().filter(|n| *n > 0))
+        .collect::<Option<Vec<usize>>>()
+        .ok_or_else(|| StoreError::Invalid(format!("'{}' is not a branch label like 1.2", spec)))?;
+
+    let mut to_visit: Vec<String> = roots.iter().rev().cloned().collect();
+    while let Some(id) = to_visit.pop() {
+        let Some(msg) = tree.get(&id) else { continue };
+        if branch_path(&id, tree) == wanted {
+            return Ok(id);
+        }
+        to_visit.extend(msg.child_ids().into_iter().rev());
+    }
+    Err(StoreError::Invalid(format!("branch {} not found", spec)))
+}
@@ -0,0 +1,83 @@
+//! `.fur/reflog.jsonl`: an append-only history of where the cursor was and
+//! what changed, one JSON object per line (newest last).
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{FurStore, Index, StoreError};
+
+/// Active thread + current message at one moment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Position {
+    pub fn of(index: &Index) -> Self {
+        Position { thread: index.active_thread.clone(), message: index.current_message.clone() }
+    }
+}
+
+/// One line of the reflog.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    pub timestamp: String,
+    /// Command that moved the cursor or changed the store (`jot`, `jump`, …)
+    pub action: String,
+    pub before: Position,
+    pub after: Position,
+    /// Short human-readable note, e.g. the message preview
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+impl FurStore {
+    pub fn reflog_path(&self) -> PathBuf {
+        self.root.join("reflog.jsonl")
+    }
+
+    /// Append an entry. Callers should hold the store lock.
+    pub fn append_reflog(
+        &self,
+        action: &str,
+        before: Position,
+        after: Position,
+        detail: impl Into<String>,
+    ) -> Result<(), StoreError> {
+        let entry = ReflogEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            action: action.to_string(),
+            before,
+            after,
+            detail: detail.into(),
+        };
+        let path = self.reflog_path();
+        let mut line = serde_json::to_string(&entry).map_err(|source| StoreError::Json { path: path.clone(), source })?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| StoreError::io(&path, e))?;
+        file.write_all(line.as_bytes()).map_err(|e| StoreError::io(&path, e))
+    }
+
+    /// Every entry, newest first. Lines that don't parse are skipped.
+    pub fn load_reflog(&self) -> Result<Vec<ReflogEntry>, StoreError> {
+        let path = self.reflog_path();
+        let contents = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(StoreError::io(&path, e)),
+        };
+        Ok(contents.lines().rev().filter_map(|l| serde_json::from_str(l).ok()).collect())
+    }
+}
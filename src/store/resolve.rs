@@ -0,0 +1,148 @@
+//! Turning what users type into full ids.
+//!
+//! Messages accept a full id, any unique prefix, or one of the symbolic
+//! names `HEAD` (the current message), `HEAD~N` (its N-th ancestor) and
+//! `root` (the root above the current message). Prefixes only need to be
+//! unique within the thread being searched, usually the active one.
+//! Threads accept a full id, a unique prefix of an indexed thread, or
+//! `HEAD` (the active thread).
+
+use std::collections::HashSet;
+
+use super::{json_files, short_id, FurStore, Index, StoreError, StoredThread};
+
+impl FurStore {
+    /// Expand a message reference to a full id in the active thread.
+    pub fn resolve_message(&self, spec: &str) -> Result<String, StoreError> {
+        let index = self.load_index()?;
+        let thread = self.active_thread(&index)?;
+        self.resolve_message_in(spec, &thread)
+    }
+
+    /// Expand a message reference to a full id among `thread`'s messages.
+    pub fn resolve_message_in(&self, spec: &str, thread: &StoredThread) -> Result<String, StoreError> {
+        let ids: HashSet<String> = self.load_tree(thread).into_keys().collect();
+        let outside = |id: &str| {
+            StoreError::Invalid(format!("Message {} is not in thread \"{}\".", short_id(id), thread.title))
+        };
+        match self.resolve_among(spec, &ids)? {
+            Some(id) if ids.contains(&id) => Ok(id),
+            Some(id) => Err(outside(&id)),
+            // Say so when the message exists, just elsewhere
+            None => match self.resolve_any_message(spec) {
+                Ok(id) => Err(outside(&id)),
+                Err(_) => Err(StoreError::MessageNotFound(spec.to_string())),
+            },
+        }
+    }
+
+    /// Expand a message reference to a full id anywhere in the store, for
+    /// commands that aren't tied to one thread (e.g. `show`).
+    pub fn resolve_any_message(&self, spec: &str) -> Result<String, StoreError> {
+        let ids: HashSet<String> = json_files(&self.messages_dir())?
+            .iter()
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .collect();
+        self.resolve_among(spec, &ids)?.ok_or_else(|| StoreError::MessageNotFound(spec.to_string()))
+    }
+
+    /// A symbolic name (wherever it points), an id in `ids`, or a prefix
+    /// unique among `ids`. `None` when nothing matches.
+    fn resolve_among(&self, spec: &str, ids: &HashSet<String>) -> Result<Option<String>, StoreError> {
+        if let Some(id) = self.resolve_symbolic(spec)? {
+            return Ok(Some(id));
+        }
+        if ids.contains(spec) {
+            return Ok(Some(spec.to_string()));
+        }
+
+        let mut matches: Vec<&String> = ids.iter().filter(|id| id.starts_with(spec)).collect();
+        matches.sort();
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(Some(matches[0].clone())),
+            _ => Err(StoreError::AmbiguousId {
+                prefix: spec.to_string(),
+                candidates: matches.iter().map(|id| self.describe_message(id)).collect(),
+            }),
+        }
+    }
+
+    /// Expand a thread reference to a full id from the index.
+    pub fn resolve_thread(&self, index: &Index, spec: &str) -> Result<String, StoreError> {
+        if spec == "HEAD" {
+            return index.active_thread.clone().ok_or(StoreError::NoActiveThread);
+        }
+        if index.threads.iter().any(|t| t == spec) {
+            return Ok(spec.to_string());
+        }
+
+        let matches: Vec<String> =
+            index.threads.iter().filter(|t| t.starts_with(spec)).cloned().collect();
+        match matches.len() {
+            0 => Err(StoreError::ThreadNotFound(spec.to_string())),
+            1 => Ok(matches.into_iter().next().unwrap()),
+            _ => Err(StoreError::AmbiguousId {
+                prefix: spec.to_string(),
+                candidates: matches
+                    .iter()
+                    .map(|id| {
+                        let title = self.load_thread(id).map(|t| t.title).unwrap_or_default();
+                        format!("{}  \"{}\"", short_id(id), title)
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    /// `HEAD`, `HEAD~N` and `root`; `None` for anything else.
+    fn resolve_symbolic(&self, spec: &str) -> Result<Option<String>, StoreError> {
+        let steps = match spec {
+            "HEAD" | "root" => 0,
+            _ => match spec.strip_prefix("HEAD~") {
+                Some(n) => match n.parse::<usize>() {
+                    Ok(n) => n,
+                    Err(_) => return Ok(None),
+                },
+                None => return Ok(None),
+            },
+        };
+
+        let index = self.load_index()?;
+        let Some(current) = index.current_message.clone() else {
+            // With no cursor, `root` still means the thread's first message
+            if spec == "root" {
+                let thread = self.active_thread(&index)?;
+                return thread
+                    .messages
+                    .first()
+                    .cloned()
+                    .map(Some)
+                    .ok_or_else(|| StoreError::Invalid("The active thread has no messages.".to_string()));
+            }
+            return Err(StoreError::Invalid(format!("No current message, so {} means nothing yet.", spec)));
+        };
+
+        let lineage = self.lineage(&current)?;
+        if spec == "root" {
+            return Ok(lineage.first().cloned());
+        }
+        if steps >= lineage.len() {
+            return Err(StoreError::Invalid(format!(
+                "{} goes past the root ({} is only {} deep).",
+                spec,
+                short_id(&current),
+                lineage.len() - 1
+            )));
+        }
+        Ok(Some(lineage[lineage.len() - 1 - steps].clone()))
+    }
+
+    /// One line per candidate: short id, avatar and first line of text.
+    fn describe_message(&self, id: &str) -> String {
+        match self.load_message(id) {
+            Ok(m) => format!("{}  [{}] {}", short_id(id), m.avatar, m.preview()),
+            Err(_) => short_id(id).to_string(),
+        }
+    }
+}
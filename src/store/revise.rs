@@ -0,0 +1,74 @@
+//! Editing messages while keeping their previous versions.
+
+use std::fs;
+use std::path::Path;
+use chrono::Utc;
+
+use super::{json_files, read_json, short_id, write_atomic, FurStore, Revision, StoreError, StoredMessage};
+
+impl FurStore {
+    /// The editable content of a message: the linked markdown file's
+    /// contents if it has one, else its text.
+    pub fn message_content(&self, msg: &StoredMessage) -> Result<String, StoreError> {
+        match msg.markdown {
+            Some(ref md) => {
+                let path = self.project_path(md);
+                fs::read_to_string(&path).map_err(|e| StoreError::io(&path, e))
+            }
+            None => Ok(msg.text.clone().unwrap_or_default()),
+        }
+    }
+
+    /// Replace a message's content, pushing the old version onto
+    /// `revisions`. Markdown-linked messages have their file rewritten.
+    /// Returns `false` (and writes nothing) if the content is unchanged.
+    /// Callers should hold the store lock.
+    pub fn revise_message(&self, msg: &mut StoredMessage, content: &str) -> Result<bool, StoreError> {
+        let old = self.message_content(msg)?;
+        if old == content {
+            return Ok(false);
+        }
+
+        let mut revision = Revision {
+            timestamp: Utc::now().to_rfc3339(),
+            text: msg.text.clone(),
+            markdown_contents: None,
+        };
+        match msg.markdown.clone() {
+            Some(md) => {
+                revision.markdown_contents = Some(old);
+                // A file still linked from elsewhere (e.g. a fork's copy) is
+                // left alone; this message gets its own copy instead
+                let target = if self.markdown_shared(msg)? { private_copy_path(&md, &msg.id) } else { md };
+                write_atomic(&self.project_path(&target), content.as_bytes())?;
+                msg.markdown = Some(target);
+            }
+            None => msg.text = Some(content.to_string()),
+        }
+        msg.revisions.push(revision);
+        self.save_message(msg)?;
+        Ok(true)
+    }
+
+    /// Whether any other message links the same markdown file.
+    fn markdown_shared(&self, msg: &StoredMessage) -> Result<bool, StoreError> {
+        for path in json_files(&self.messages_dir())? {
+            let Ok(other) = read_json::<StoredMessage>(&path) else { continue };
+            if other.id != msg.id && other.markdown.is_some() && other.markdown == msg.markdown {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// `notes/idea.md` → `notes/idea-<short id>.md`
+fn private_copy_path(md: &str, msg_id: &str) -> String {
+    let path = Path::new(md);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, short_id(msg_id), ext.to_string_lossy()),
+        None => format!("{}-{}", stem, short_id(msg_id)),
+    };
+    path.with_file_name(name).to_string_lossy().to_string()
+}
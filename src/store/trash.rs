@@ -0,0 +1,150 @@
+//! `.fur/trash/`: removed messages, kept until restored.
+//!
+//! Each removal gets `trash/<message id>/` holding an `entry.json`
+//! ([`TrashEntry`]) plus the removed message files.
+
+use std::fs;
+use std::path::PathBuf;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::graph::Slot;
+use super::{read_json, short_id, write_json, FurStore, StoreError};
+
+/// One `fur rm`, as recorded in the trash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// The removed message (root of the removed subtree)
+    pub id: String,
+    pub thread: String,
+    /// Where it hung before removal
+    pub slot: Slot,
+    pub deleted_at: String,
+    /// Message files moved into the trash, parent first. Messages another
+    /// thread still reaches are left in place and not listed.
+    pub messages: Vec<String>,
+}
+
+impl FurStore {
+    pub fn trash_dir(&self) -> PathBuf {
+        self.root().join("trash")
+    }
+
+    fn trash_entry_dir(&self, id: &str) -> PathBuf {
+        self.trash_dir().join(id)
+    }
+
+    /// Remove a message (and with `recursive`, its whole subtree) from its
+    /// thread and move the files into the trash. Callers should hold the
+    /// store lock.
+    pub fn remove_message(&self, msg_id: &str, recursive: bool) -> Result<TrashEntry, StoreError> {
+        let mut index = self.load_index()?;
+        let mut thread = self
+            .thread_containing(&index, msg_id)
+            .ok_or_else(|| StoreError::MessageNotFound(msg_id.to_string()))?;
+
+        let msg = self.load_message(msg_id)?;
+        let children = msg.child_ids().len();
+        if children > 0 && !recursive {
+            return Err(StoreError::HasChildren { id: msg_id.to_string(), count: children });
+        }
+
+        let slot = self.detach(&mut thread, msg_id)?;
+        let subtree = self.subtree_ids(msg_id);
+        let reachable = self.reachable_messages()?;
+
+        let dir = self.trash_entry_dir(msg_id);
+        if dir.exists() {
+            self.journal_touch_dir(&dir)?;
+            fs::remove_dir_all(&dir).map_err(|e| StoreError::io(&dir, e))?;
+        }
+        fs::create_dir_all(&dir).map_err(|e| StoreError::io(&dir, e))?;
+
+        let mut moved = Vec::new();
+        for mid in subtree.iter().filter(|m| !reachable.contains(*m)) {
+            let from = self.message_path(mid);
+            if !from.exists() {
+                continue;
+            }
+            let to = dir.join(format!("{}.json", mid));
+            self.journal_touch(&from)?;
+            self.journal_touch(&to)?;
+            fs::rename(&from, &to).map_err(|e| StoreError::io(&from, e))?;
+            moved.push(mid.clone());
+        }
+
+        let entry = TrashEntry {
+            id: msg_id.to_string(),
+            thread: thread.id.clone(),
+            slot,
+            deleted_at: Utc::now().to_rfc3339(),
+            messages: moved,
+        };
+        self.journal_touch(&dir.join("entry.json"))?;
+        write_json(&dir.join("entry.json"), &entry)?;
+
+        if index.current_message.as_ref().is_some_and(|c| subtree.contains(c)) {
+            index.current_message = entry.slot.parent.clone();
+            self.save_index(&index)?;
+        }
+        Ok(entry)
+    }
+
+    /// Everything in the trash, oldest removal first.
+    pub fn list_trash(&self) -> Result<Vec<TrashEntry>, StoreError> {
+        let dir = self.trash_dir();
+        let Ok(read) = fs::read_dir(&dir) else {
+            return Ok(vec![]);
+        };
+        let mut entries = Vec::new();
+        for item in read {
+            let item = item.map_err(|e| StoreError::io(&dir, e))?;
+            let path = item.path().join("entry.json");
+            if path.exists() {
+                entries.push(read_json::<TrashEntry>(&path)?);
+            }
+        }
+        entries.sort_by(|a, b| a.deleted_at.cmp(&b.deleted_at));
+        Ok(entries)
+    }
+
+    /// Put a trashed message (or subtree) back where it was. `id` may be a
+    /// unique prefix. Callers should hold the store lock.
+    pub fn restore(&self, id: &str) -> Result<TrashEntry, StoreError> {
+        let mut matches: Vec<TrashEntry> =
+            self.list_trash()?.into_iter().filter(|e| e.id.starts_with(id)).collect();
+        let entry = match matches.len() {
+            0 => return Err(StoreError::NotInTrash(id.to_string())),
+            1 => matches.remove(0),
+            _ => {
+                return Err(StoreError::AmbiguousId {
+                    prefix: id.to_string(),
+                    candidates: matches
+                        .iter()
+                        .map(|e| format!("{}  removed {}", short_id(&e.id), e.deleted_at))
+                        .collect(),
+                })
+            }
+        };
+
+        let mut thread = self.load_thread(&entry.thread)?;
+        if let Some(ref parent) = entry.slot.parent {
+            if !self.message_exists(parent) {
+                return Err(StoreError::MessageNotFound(parent.clone()));
+            }
+        }
+
+        let dir = self.trash_entry_dir(&entry.id);
+        for mid in &entry.messages {
+            let from = dir.join(format!("{}.json", mid));
+            let to = self.message_path(mid);
+            self.journal_touch(&from)?;
+            self.journal_touch(&to)?;
+            fs::rename(&from, &to).map_err(|e| StoreError::io(&from, e))?;
+        }
+        self.attach(&mut thread, &entry.id, &entry.slot)?;
+        self.journal_touch_dir(&dir)?;
+        fs::remove_dir_all(&dir).map_err(|e| StoreError::io(&dir, e))?;
+        Ok(entry)
+    }
+}
@@ -23,12 +23,12 @@ fn setup_fur(tmp: &std::path::Path) {
 #[test]
 fn avatar_view_lists_main_and_secondary() {
     let tmp = tempdir().unwrap();
-    std::env::set_current_dir(&tmp).unwrap();
     setup_fur(tmp.path());
 
     // Run `fur avatar --view` (same as `fur avatar`)
     Command::cargo_bin("fur").unwrap()
-        .args(&["avatar", "--view"])
+        .current_dir(&tmp)
+        .args(["avatar", "--view"])
         .assert()
         .success()
         .stdout(predicate::str::contains("Avatars")) // sleek header
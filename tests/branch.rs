@@ -0,0 +1,77 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+
+mod common;
+use common::{fur, init_store};
+
+#[test]
+fn branch_opens_groups_that_following_jots_fill() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Branches"]).success();
+    fur(tmp.path(), &["jot", "question"]).success();
+    let question = store.load_index().unwrap().current_message.unwrap();
+
+    fur(tmp.path(), &["branch"]).success().stdout(contains("Opened branch 1"));
+    fur(tmp.path(), &["status"]).success().stdout(contains("Open branch:"));
+    fur(tmp.path(), &["jot", "plan a"]).success().stdout(contains("in branch 1"));
+    fur(tmp.path(), &["jot", "plan a, step 2"]).success();
+
+    fur(tmp.path(), &["branch", &question[..8]]).success().stdout(contains("Opened branch 2"));
+    fur(tmp.path(), &["jot", "plan b"]).success();
+
+    // --branch adds to an existing group under the current message
+    fur(tmp.path(), &["jump", "--id", &question[..8]]).success();
+    fur(tmp.path(), &["jot", "plan a, step 3", "--branch", "1"]).success();
+    fur(tmp.path(), &["jot", "nope", "--branch", "5"]).failure().stderr(contains("no branch 5"));
+
+    let q = store.load_message(&question).unwrap();
+    let texts = |group: &Vec<String>| -> Vec<String> {
+        group.iter().map(|id| store.load_message(id).unwrap().text.unwrap()).collect()
+    };
+    assert_eq!(q.branches.len(), 2);
+    assert_eq!(texts(&q.branches[0]), ["plan a", "plan a, step 2", "plan a, step 3"]);
+    assert_eq!(texts(&q.branches[1]), ["plan b"]);
+    assert_eq!(q.children.len(), 4);
+    for id in q.child_ids() {
+        assert_eq!(store.load_message(&id).unwrap().parent, Some(question.clone()));
+    }
+
+    // Jumping out of the group closes it: plain jots go back to the roots
+    fur(tmp.path(), &["jump", "--branch", "2"]).success();
+    fur(tmp.path(), &["jot", "new topic"]).success();
+    let index = store.load_index().unwrap();
+    assert_eq!(store.active_thread(&index).unwrap().messages.len(), 2);
+}
+
+#[test]
+fn branches_stay_inside_the_active_thread() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "A"]).success();
+    fur(tmp.path(), &["jot", "in a"]).success();
+    let a_msg = store.load_index().unwrap().current_message.unwrap();
+    fur(tmp.path(), &["new", "B"]).success();
+    fur(tmp.path(), &["jot", "in b"]).success();
+
+    // Naming another thread's message is refused
+    fur(tmp.path(), &["branch", &a_msg[..8]]).failure().stderr(contains("not in thread \"B\""));
+    fur(tmp.path(), &["jot", "into branch"]).success();
+    let index = store.load_index().unwrap();
+    let b = store.active_thread(&index).unwrap();
+    assert!(store.load_tree(&b).contains_key(index.current_message.as_deref().unwrap()));
+    fur(tmp.path(), &["status"]).success().stdout(contains("into branch"));
+
+    // A stale open branch under A doesn't pull B's jots across
+    let mut index = store.load_index().unwrap();
+    index.current_message = Some(a_msg.clone());
+    index.open_branch = Some(fur_cli::store::OpenBranch { parent: a_msg.clone(), group: None });
+    store.save_index(&index).unwrap();
+    fur(tmp.path(), &["jot", "stray"]).failure().stderr(contains("not in thread \"B\""));
+    fur(tmp.path(), &["jot", "stray", "--branch", "1"]).failure().stderr(contains("not in thread \"B\""));
+
+    assert!(store.load_message(&a_msg).unwrap().children.is_empty());
+    assert_eq!(store.load_index().unwrap().open_branch, None);
+}
@@ -24,12 +24,12 @@ fn setup_fur(tmp: &std::path::Path) {
 #[test]
 fn chat_creates_file_and_message() {
     let tmp = tempdir().unwrap();
-    std::env::set_current_dir(&tmp).unwrap();
     setup_fur(tmp.path());
 
     // 1. Start a new thread so we have an active context
     Command::cargo_bin("fur").unwrap()
-        .args(&["new", "Chat Test"])
+        .current_dir(&tmp)
+        .args(["new", "Chat Test"])
         .assert()
         .success()
         .stdout(contains("[NEW] Thread created"));
@@ -37,7 +37,8 @@ fn chat_creates_file_and_message() {
     // 2. Simulate running `fur chat` with multi-line input
     let input = "# Title\n\nHello world from chat!\n";
     let mut cmd = Command::cargo_bin("fur").unwrap();
-    cmd.arg("chat")
+    cmd.current_dir(&tmp)
+        .arg("chat")
         .arg("gpt5")
         .write_stdin(input)
         .assert()
@@ -0,0 +1,87 @@
+//! Helpers shared by the CLI integration tests (`mod common;`).
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::Command;
+use fur_cli::store::{FurStore, StoredMessage, StoredThread};
+
+/// Helper: a `fur` command inside `dir`, ignoring the caller's `FUR_DIR`
+/// and `VISUAL`
+pub fn fur_cmd(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("fur").unwrap();
+    cmd.current_dir(dir).env_remove("FUR_DIR").env_remove("VISUAL");
+    cmd
+}
+
+/// Helper: run `fur` inside `dir`
+pub fn fur(dir: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    fur_cmd(dir).args(args).assert()
+}
+
+/// Helper: bootstrap `.fur` in `dir` with `me` as the main avatar
+pub fn init_store(dir: &Path) -> FurStore {
+    init_store_with_avatars(dir, r#"{"main":"me"}"#)
+}
+
+/// Helper: bootstrap `.fur` in `dir` with the given `avatars.json`
+pub fn init_store_with_avatars(dir: &Path, avatars: &str) -> FurStore {
+    let store = FurStore::init(dir.join(".fur")).unwrap();
+    fs::write(dir.join(".fur/avatars.json"), avatars).unwrap();
+    store
+}
+
+/// Helper: a message by `me`, with `id` in place of a generated one
+pub fn message(id: Option<&str>, parent: Option<&str>, text: &str) -> StoredMessage {
+    let mut m = StoredMessage::new("me", parent.map(str::to_string));
+    if let Some(id) = id {
+        m.id = id.to_string();
+    }
+    m.text = Some(text.to_string());
+    m
+}
+
+/// Helper: save a thread of the given root messages and list it in the index
+pub fn save_thread(store: &FurStore, title: &str, roots: &[&StoredMessage]) -> StoredThread {
+    let mut thread = StoredThread::new(title);
+    for m in roots {
+        store.save_message(m).unwrap();
+        thread.messages.push(m.id.clone());
+    }
+    store.save_thread(&thread).unwrap();
+    let mut index = store.load_index().unwrap();
+    index.threads.push(thread.id.clone());
+    store.save_index(&index).unwrap();
+    thread
+}
+
+/// Helper: save `msg` under its (already saved) parent, in branch group
+/// `group` or as a plain reply
+pub fn attach(store: &FurStore, msg: &StoredMessage, group: Option<usize>) {
+    let mut parent = store.load_message(msg.parent.as_deref().unwrap()).unwrap();
+    parent.children.push(msg.id.clone());
+    if let Some(k) = group {
+        if k == parent.branches.len() {
+            parent.branches.push(vec![]);
+        }
+        parent.branches[k].push(msg.id.clone());
+    }
+    store.save_message(msg).unwrap();
+    store.save_message(&parent).unwrap();
+}
+
+/// Helper: jot `text` as a reply to `parent`
+pub fn reply(store: &FurStore, parent: &str, text: &str) -> String {
+    let m = message(None, Some(parent), text);
+    attach(store, &m, None);
+    m.id
+}
+
+/// Helper: make `thread` the active one, at `current`
+pub fn activate(store: &FurStore, thread: &str, current: Option<&str>) {
+    let mut index = store.load_index().unwrap();
+    index.active_thread = Some(thread.to_string());
+    index.current_message = current.map(str::to_string);
+    store.save_index(&index).unwrap();
+}
@@ -0,0 +1,40 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+
+mod common;
+use common::{fur, init_store};
+
+#[test]
+fn each_thread_keeps_its_own_cursor() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "First"]).success();
+    fur(tmp.path(), &["jot", "one"]).success();
+    fur(tmp.path(), &["jot", "two", "--parent", "HEAD"]).success();
+    fur(tmp.path(), &["jump", "--past", "1"]).success();
+    let index = store.load_index().unwrap();
+    let first = index.active_thread.clone().unwrap();
+    let first_cursor = index.current_message.clone().unwrap();
+
+    // A new thread starts without a cursor; the old one remembers its place
+    fur(tmp.path(), &["new", "Second"]).success();
+    let index = store.load_index().unwrap();
+    let second = index.active_thread.clone().unwrap();
+    assert_eq!(index.current_message, None);
+    assert_eq!(store.load_thread(&first).unwrap().cursor, Some(first_cursor.clone()));
+
+    fur(tmp.path(), &["jot", "elsewhere"]).success();
+    let second_cursor = store.load_index().unwrap().current_message.unwrap();
+
+    fur(tmp.path(), &["thread", &first[..8]]).success().stdout(contains("resumed at"));
+    assert_eq!(store.load_index().unwrap().current_message, Some(first_cursor.clone()));
+
+    fur(tmp.path(), &["status"])
+        .success()
+        .stdout(contains("Thread cursors:"))
+        .stdout(contains(&second_cursor[..8]));
+
+    fur(tmp.path(), &["thread", &second[..8]]).success();
+    assert_eq!(store.load_index().unwrap().current_message, Some(second_cursor));
+}
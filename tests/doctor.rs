@@ -0,0 +1,135 @@
+use std::fs;
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, Issue};
+
+/// Helper: a store with one of every kind of drift
+fn setup_broken_fur(tmp: &std::path::Path) -> std::path::PathBuf {
+    let fur_dir = tmp.join(".fur");
+    fs::create_dir_all(fur_dir.join("threads")).unwrap();
+    fs::create_dir_all(fur_dir.join("messages")).unwrap();
+    fs::write(fur_dir.join("index.json"), r#"{
+        "threads": ["t1", "gone"],
+        "active_thread": "t1",
+        "current_message": "vanished",
+        "schema_version": "0.3"
+    }"#).unwrap();
+    fs::write(fur_dir.join("threads/t1.json"), r#"{
+        "id": "t1", "title": "Broken", "tags": [], "messages": ["m1", "lost"]
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m1.json"), r#"{
+        "id": "m1", "avatar": "me", "text": "root",
+        "children": ["m2", "deleted"], "branches": [["m3"]], "timestamp": ""
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m2.json"), r#"{
+        "id": "m2", "avatar": "me", "text": "wrong parent", "parent": "elsewhere",
+        "children": [], "branches": [], "timestamp": ""
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m3.json"), r#"{
+        "id": "m3", "avatar": "me", "markdown": "notes/missing.md", "parent": "m1",
+        "children": [], "branches": [], "timestamp": ""
+    }"#).unwrap();
+    fur_dir
+}
+
+#[test]
+fn doctor_reports_and_repairs_drift() {
+    let tmp = tempdir().unwrap();
+    let store = FurStore::open(setup_broken_fur(tmp.path())).unwrap();
+
+    let issues = store.check().unwrap();
+    let t = |s: &str| s.to_string();
+    assert!(issues.contains(&Issue::MissingThread { thread: t("gone") }));
+    assert!(issues.contains(&Issue::CurrentMessageMissing { message: t("vanished") }));
+    assert!(issues.contains(&Issue::MissingRoot { thread: t("t1"), message: t("lost") }));
+    assert!(issues.contains(&Issue::DanglingChild { thread: t("t1"), parent: t("m1"), child: t("deleted") }));
+    assert!(issues.contains(&Issue::ParentMismatch {
+        thread: t("t1"),
+        message: t("m2"),
+        expected: Some(t("m1")),
+        found: Some(t("elsewhere")),
+    }));
+    assert!(issues.contains(&Issue::BranchNotInChildren { thread: t("t1"), parent: t("m1"), child: t("m3") }));
+    assert!(issues.contains(&Issue::MissingMarkdown {
+        thread: t("t1"),
+        message: t("m3"),
+        path: t("notes/missing.md"),
+    }));
+
+    let fixed = store.repair(&issues).unwrap();
+    assert_eq!(fixed, issues.len() - 1);
+
+    // Only the unfixable markdown link is left
+    let remaining = store.check().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert!(matches!(remaining[0], Issue::MissingMarkdown { .. }));
+
+    let m1 = store.load_message("m1").unwrap();
+    assert_eq!(m1.children, vec![t("m2"), t("m3")]);
+    assert_eq!(store.load_message("m2").unwrap().parent.as_deref(), Some("m1"));
+    assert_eq!(store.load_index().unwrap().threads, vec![t("t1")]);
+}
+
+#[test]
+fn missing_active_thread_is_reported_once_and_switched_away_from() {
+    let tmp = tempdir().unwrap();
+    let fur_dir = tmp.path().join(".fur");
+    fs::create_dir_all(fur_dir.join("threads")).unwrap();
+    fs::create_dir_all(fur_dir.join("messages")).unwrap();
+    fs::write(fur_dir.join("index.json"), r#"{
+        "threads": ["t1", "gone"],
+        "active_thread": "gone",
+        "current_message": "vanished",
+        "schema_version": "0.3"
+    }"#).unwrap();
+    fs::write(fur_dir.join("threads/t1.json"), r#"{
+        "id": "t1", "title": "Kept", "tags": [], "messages": ["m1"], "cursor": "m1"
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m1.json"), r#"{
+        "id": "m1", "avatar": "me", "text": "root", "children": [], "branches": [], "timestamp": ""
+    }"#).unwrap();
+    let store = FurStore::open(fur_dir).unwrap();
+
+    let issues = store.check().unwrap();
+    let t = |s: &str| s.to_string();
+    assert_eq!(issues, vec![
+        Issue::MissingThread { thread: t("gone") },
+        Issue::CurrentMessageMissing { message: t("vanished") },
+    ]);
+
+    assert_eq!(store.repair(&issues).unwrap(), 2);
+    assert!(store.check().unwrap().is_empty());
+    let index = store.load_index().unwrap();
+    assert_eq!(index.threads, vec![t("t1")]);
+    assert_eq!(index.active_thread.as_deref(), Some("t1"));
+    // The thread's saved cursor comes back with it
+    assert_eq!(index.current_message.as_deref(), Some("m1"));
+}
+
+#[test]
+fn unlisted_active_thread_is_listed_again() {
+    let tmp = tempdir().unwrap();
+    let fur_dir = tmp.path().join(".fur");
+    fs::create_dir_all(fur_dir.join("threads")).unwrap();
+    fs::create_dir_all(fur_dir.join("messages")).unwrap();
+    fs::write(fur_dir.join("index.json"), r#"{
+        "threads": ["t1"],
+        "active_thread": "t2",
+        "schema_version": "0.3"
+    }"#).unwrap();
+    for tid in ["t1", "t2"] {
+        fs::write(
+            fur_dir.join(format!("threads/{}.json", tid)),
+            format!(r#"{{ "id": "{}", "title": "{}", "tags": [], "messages": [] }}"#, tid, tid),
+        ).unwrap();
+    }
+    let store = FurStore::open(fur_dir).unwrap();
+
+    let issues = store.check().unwrap();
+    assert_eq!(issues, vec![Issue::ActiveThreadUnlisted { thread: "t2".to_string() }]);
+    assert_eq!(store.repair(&issues).unwrap(), 1);
+    assert!(store.check().unwrap().is_empty());
+
+    let index = store.load_index().unwrap();
+    assert_eq!(index.threads, ["t1", "t2"]);
+    assert_eq!(index.active_thread.as_deref(), Some("t2"));
+}
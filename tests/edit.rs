@@ -0,0 +1,60 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+use std::fs;
+
+mod common;
+use common::{fur, fur_cmd, init_store};
+
+#[test]
+fn edit_keeps_revisions_and_show_lists_them() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    fur(tmp.path(), &["new", "Edits"]).success();
+    fur(tmp.path(), &["jot", "a rough draft"]).success();
+
+    let id = store.load_index().unwrap().current_message.unwrap();
+    let prefix = &id[..6];
+
+    fur_cmd(tmp.path())
+        .env("EDITOR", "sed -i s/rough/final/")
+        .args(["edit", prefix])
+        .assert()
+        .success()
+        .stdout(contains("revision 2"));
+
+    let msg = store.load_message(&id).unwrap();
+    assert_eq!(msg.text.as_deref(), Some("a final draft"));
+    assert_eq!(msg.revisions.len(), 1);
+    assert_eq!(msg.revisions[0].text.as_deref(), Some("a rough draft"));
+
+    // An editor that changes nothing doesn't add a revision
+    fur_cmd(tmp.path()).env("EDITOR", "true").args(["edit", prefix]).assert().success().stdout(contains("No changes"));
+    assert_eq!(store.load_message(&id).unwrap().revisions.len(), 1);
+
+    fur(tmp.path(), &["show", prefix, "--revisions"])
+        .success()
+        .stdout(contains("v1"))
+        .stdout(contains("a rough draft"))
+        .stdout(contains("v2"))
+        .stdout(contains("(current)"));
+
+    fur(tmp.path(), &["timeline", "--verbose"])
+        .success()
+        .stdout(contains("✏️ edited 1×"));
+}
+
+#[test]
+fn edit_rewrites_linked_markdown() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    fs::write(tmp.path().join("notes.md"), "# Old heading\n").unwrap();
+    fur(tmp.path(), &["new", "Docs"]).success();
+    fur(tmp.path(), &["jot", "--markdown", "notes.md"]).success();
+
+    let id = store.load_index().unwrap().current_message.unwrap();
+    fur_cmd(tmp.path()).env("EDITOR", "sed -i s/Old/New/").args(["edit", &id]).assert().success();
+
+    assert_eq!(fs::read_to_string(tmp.path().join("notes.md")).unwrap(), "# New heading\n");
+    let msg = store.load_message(&id).unwrap();
+    assert_eq!(msg.revisions[0].markdown_contents.as_deref(), Some("# Old heading\n"));
+}
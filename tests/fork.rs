@@ -0,0 +1,89 @@
+use tempfile::tempdir;
+use std::fs;
+
+mod common;
+use common::{fur, fur_cmd, init_store};
+
+#[test]
+fn fork_copies_messages_instead_of_sharing_them() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    fs::write(tmp.path().join("plan.md"), "original plan\n").unwrap();
+
+    fur(tmp.path(), &["new", "Source"]).success();
+    fur(tmp.path(), &["jot", "question"]).success();
+    let index = store.load_index().unwrap();
+    let source = index.active_thread.unwrap();
+    let root = index.current_message.unwrap();
+    fur(tmp.path(), &["jot", "--markdown", "plan.md", "--parent", &root]).success();
+    let answer = store.load_index().unwrap().current_message.unwrap();
+
+    fur(tmp.path(), &["fork"]).success();
+    let index = store.load_index().unwrap();
+    let fork = store.load_thread(index.active_thread.as_deref().unwrap()).unwrap();
+    assert_eq!(fork.forked_from.as_deref(), Some(source.as_str()));
+
+    // Fresh ids with provenance, and the cursor follows onto the copy
+    let root_copy = store.load_message(&fork.messages[0]).unwrap();
+    assert_ne!(root_copy.id, root);
+    assert_eq!(root_copy.copy_of.as_deref(), Some(root.as_str()));
+    let answer_copy = store.load_message(&root_copy.children[0]).unwrap();
+    assert_eq!(answer_copy.copy_of.as_deref(), Some(answer.as_str()));
+    assert_eq!(answer_copy.parent.as_deref(), Some(root_copy.id.as_str()));
+    assert_eq!(index.current_message.as_deref(), Some(answer_copy.id.as_str()));
+
+    // Replying and editing in the fork leaves the source alone
+    fur(tmp.path(), &["jot", "fork only", "--parent", &root_copy.id]).success();
+    fur_cmd(tmp.path())
+        .env("EDITOR", "sed -i s/original/revised/")
+        .args(["edit", &answer_copy.id])
+        .assert()
+        .success();
+
+    assert_eq!(store.load_message(&root).unwrap().children, vec![answer.clone()]);
+    assert_eq!(store.load_message(&root_copy.id).unwrap().children.len(), 2);
+    assert_eq!(fs::read_to_string(tmp.path().join("plan.md")).unwrap(), "original plan\n");
+    let edited = store.load_message(&answer_copy.id).unwrap();
+    let private = store.project_path(edited.markdown.as_deref().unwrap());
+    assert_eq!(fs::read_to_string(private).unwrap(), "revised plan\n");
+    assert!(store.check().unwrap().is_empty());
+}
+
+#[test]
+fn fork_at_copies_only_the_lineage() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Source"]).success();
+    fur(tmp.path(), &["jot", "root"]).success();
+    let root = store.load_index().unwrap().current_message.unwrap();
+    fur(tmp.path(), &["jot", "side reply", "--parent", &root]).success();
+    fur(tmp.path(), &["jot", "main reply", "--parent", &root]).success();
+    let reply = store.load_index().unwrap().current_message.unwrap();
+    fur(tmp.path(), &["jot", "deeper", "--parent", &reply]).success();
+    let source = store.load_index().unwrap().active_thread.unwrap();
+
+    fur(tmp.path(), &["fork", "--at", &reply[..8], "--title", "Tangent"]).success();
+
+    let index = store.load_index().unwrap();
+    let fork = store.load_thread(index.active_thread.as_deref().unwrap()).unwrap();
+    assert_eq!(fork.title, "Tangent");
+    assert_eq!(fork.forked_from.as_deref(), Some(source.as_str()));
+    assert_eq!(fork.forked_at.as_deref(), Some(reply.as_str()));
+
+    // root → main reply, without the side reply or anything below
+    let tree = store.load_tree(&fork);
+    assert_eq!(tree.len(), 2);
+    let root_copy = &tree[&fork.messages[0]];
+    assert_eq!(root_copy.copy_of.as_deref(), Some(root.as_str()));
+    let tip = &tree[&root_copy.children[0]];
+    assert_eq!(tip.copy_of.as_deref(), Some(reply.as_str()));
+    assert!(tip.children.is_empty());
+    assert_eq!(index.current_message.as_deref(), Some(tip.id.as_str()));
+
+    // Without a value, --at uses the current message
+    fur(tmp.path(), &["fork", "--at"]).success();
+    let fork2 = store.load_thread(store.load_index().unwrap().active_thread.as_deref().unwrap()).unwrap();
+    assert_eq!(fork2.forked_at.as_deref(), Some(tip.id.as_str()));
+    assert!(store.check().unwrap().is_empty());
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use tempfile::tempdir;
+use fur_cli::frs::ast::ScriptItem;
+use fur_cli::frs::parser::parse_source;
+use fur_cli::frs::persist_frs;
+use fur_cli::store::StoreError;
+
+mod common;
+use common::{fur, init_store};
+
+const SCRIPT: &str = r#"new "Three Weeks"
+user = me
+
+jot @2025-03-01T10:00Z id=kickoff #plan #plan "Let's start."
+branch {
+  jot bot @2025-03-08 #followup "A week later."
+  jot id=wrap-up @2025-03-22T16:30:00+01:00 <<<
+    Done.
+    >>>
+}
+jot id=kickoff @yesterday "Clashes."
+jot id="bad id" "Malformed."
+"#;
+
+#[test]
+fn attributes_are_parsed_and_checked() {
+    let thread = parse_source(SCRIPT, &json!({})).unwrap();
+    let ScriptItem::Message(first) = &thread.items[0] else { panic!() };
+    assert_eq!(first.timestamp.as_deref(), Some("2025-03-01T10:00:00+00:00"));
+    assert_eq!(first.id.as_deref(), Some("kickoff"));
+    assert_eq!(first.tags, ["plan"]);
+    assert_eq!(first.avatar, "me");
+
+    let week = &first.branches[0][0];
+    assert_eq!((week.avatar.as_str(), week.timestamp.as_deref()), ("bot", Some("2025-03-08T00:00:00+00:00")));
+    let wrap = &first.branches[0][1];
+    assert_eq!(wrap.text.as_deref(), Some("Done."));
+    assert_eq!(wrap.timestamp.as_deref(), Some("2025-03-22T16:30:00+01:00"));
+
+    let found: Vec<(usize, &str)> = thread.warnings.iter().map(|w| (w.span.unwrap().line, w.message.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (11, "duplicate message id `kickoff` (first used on line 4)"),
+            (11, "could not read timestamp `@yesterday`"),
+            (12, "invalid message id `bad id`"),
+        ]
+    );
+}
+
+#[test]
+fn persist_honors_ids_and_timestamps() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let thread = parse_source(SCRIPT, &json!({})).unwrap();
+    persist_frs(&store, &thread).unwrap();
+
+    let kickoff = store.load_message("kickoff").unwrap();
+    assert_eq!(kickoff.timestamp, "2025-03-01T10:00:00+00:00");
+    assert_eq!(kickoff.tags, ["plan"]);
+    assert_eq!(store.load_message("wrap-up").unwrap().parent.as_deref(), Some("kickoff"));
+
+    // The same ids under another title would clobber stored messages
+    let renamed = SCRIPT.replace("Three Weeks", "Other");
+    let err = persist_frs(&store, &parse_source(&renamed, &json!({})).unwrap()).unwrap_err();
+    assert!(matches!(err, StoreError::Invalid(ref m) if m.contains("kickoff")), "{}", err);
+}
+
+#[test]
+fn short_ids_display_everywhere() {
+    let tmp = tempdir().unwrap();
+    init_store(tmp.path());
+    std::fs::write(
+        tmp.path().join("short.frs"),
+        "new \"Short\"\nuser = me\njot id=a \"hello\"\nbranch {\n  jot id=b \"reply\"\n}\nstore\ntree\nstatus\n",
+    )
+    .unwrap();
+
+    for args in [&["run", "short.frs"][..], &["jump", "--id", "a"], &["tree"], &["status"]] {
+        fur(tmp.path(), args).success();
+    }
+}
@@ -1,27 +1,28 @@
 use tempfile::tempdir;
 use std::fs;
-use std::env;
 use fur_cli::commands::run::run_frs;
+use fur_cli::store::FurStore;
 
 #[test]
 fn run_basic_script() {
     let tmp = tempdir().unwrap();
-    env::set_current_dir(&tmp).unwrap();
+    let fur_dir = tmp.path().join(".fur");
 
     // Bootstrap .fur
-    fs::create_dir_all(".fur/threads").unwrap();
-    fs::create_dir_all(".fur/messages").unwrap();
-    fs::create_dir_all(".fur/tmp").unwrap();
-    fs::write(".fur/index.json", r#"{
+    fs::create_dir_all(fur_dir.join("threads")).unwrap();
+    fs::create_dir_all(fur_dir.join("messages")).unwrap();
+    fs::create_dir_all(fur_dir.join("tmp")).unwrap();
+    fs::write(fur_dir.join("index.json"), r#"{
         "threads": [],
         "active_thread": null,
         "current_message": null,
         "schema_version": "0.2"
     }"#).unwrap();
-    fs::write(".fur/avatars.json", r#"{"main": "test"}"#).unwrap();
+    fs::write(fur_dir.join("avatars.json"), r#"{"main": "test"}"#).unwrap();
 
     // Write test script
-    fs::write("test.frs", r#"
+    let script_path = tmp.path().join("test.frs");
+    fs::write(&script_path, r#"
         new "Test Script"
         user = test
         jot "hello world"
@@ -29,5 +30,6 @@ fn run_basic_script() {
         status
     "#).unwrap();
 
-    run_frs("test.frs");
+    let store = FurStore::open(&fur_dir).unwrap();
+    run_frs(&store, script_path.to_str().unwrap()).unwrap();
 }
@@ -1,12 +1,11 @@
 use std::fs;
 use tempfile::tempdir;
 use fur_cli::commands::run::run_frs;
+use fur_cli::store::FurStore;
 
 #[test]
 fn run_branch_script() {
     let tmp = tempdir().unwrap();
-    std::env::set_current_dir(&tmp).unwrap();
-
     std::fs::create_dir_all(tmp.path().join(".fur/threads")).unwrap();
     std::fs::create_dir_all(tmp.path().join(".fur/messages")).unwrap();
     std::fs::write(tmp.path().join(".fur/index.json"), r#"{
@@ -15,7 +14,7 @@ fn run_branch_script() {
         "current_message": null,
         "schema_version": "0.2"
     }"#).unwrap();
-    fs::write(".fur/avatars.json", r#"{"main":"ai"}"#).unwrap();
+    fs::write(tmp.path().join(".fur/avatars.json"), r#"{"main":"ai"}"#).unwrap();
 
     let script = r#"
         new "Branch Test"
@@ -28,10 +27,12 @@ fn run_branch_script() {
         }
         store
     "#;
-    fs::write("branch.frs", script).unwrap();
+    let script_path = tmp.path().join("branch.frs");
+    fs::write(&script_path, script).unwrap();
 
-    run_frs("branch.frs");
+    let store = FurStore::open(tmp.path().join(".fur")).unwrap();
+    run_frs(&store, script_path.to_str().unwrap()).unwrap();
 
-    assert!(fs::read_dir(".fur/threads").unwrap().count() == 1);
-    assert!(fs::read_dir(".fur/messages").unwrap().count() >= 2);
+    assert!(fs::read_dir(store.threads_dir()).unwrap().count() == 1);
+    assert!(fs::read_dir(store.messages_dir()).unwrap().count() >= 2);
 }
@@ -0,0 +1,113 @@
+use predicates::prelude::*;
+use predicates::str::contains;
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+use fur_cli::frs::ast::ScriptItem;
+use fur_cli::frs::parser::parse_source;
+
+mod common;
+use common::{fur_cmd, init_store};
+
+#[test]
+fn recoverable_problems_are_warnings_with_real_positions() {
+    let script = "\
+# a comment, then a blank line
+
+new \"Diagnostics\"
+user = me
+branch {
+  jot \"orphan\"
+}
+
+this line means nothing
+jot \"kept\"
+jot \"never closed
+store
+";
+    let thread = parse_source(script, &json!({})).unwrap();
+
+    let found: Vec<(usize, usize, &str)> = thread
+        .warnings
+        .iter()
+        .map(|w| (w.span.unwrap().line, w.span.unwrap().col, w.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (5, 1, "`branch` with no preceding jot"),
+            (9, 1, "unrecognized line"),
+            (11, 5, "unterminated string"),
+        ]
+    );
+    assert_eq!(thread.warnings[1].snippet, "this line means nothing");
+
+    let texts: Vec<&str> = thread
+        .items
+        .iter()
+        .filter_map(|i| if let ScriptItem::Message(m) = i { m.text.as_deref() } else { None })
+        .collect();
+    assert_eq!(texts, ["kept", "never closed"]);
+    assert!(matches!(thread.items.last(), Some(ScriptItem::Command(c)) if c.line_number == 12));
+}
+
+#[test]
+fn unclosed_blocks_are_named_by_their_keyword() {
+    let script = "new \"Blocks\"\nuser = me\njot \"q\"\nreply {\n  jot \"a\"\n  branch {\n    jot \"b\"\n";
+    let thread = parse_source(script, &json!({})).unwrap();
+
+    let found: Vec<(usize, &str)> =
+        thread.warnings.iter().map(|w| (w.span.unwrap().line, w.message.as_str())).collect();
+    assert_eq!(found, [(4, "unclosed `reply {`"), (6, "unclosed `branch {`")]);
+}
+
+#[test]
+fn fatal_problems_are_errors_not_panics() {
+    let errors = parse_source("user = me\njot \"hi\"\n", &json!({})).unwrap_err();
+    assert!(errors.iter().any(|d| d.is_error() && d.message.contains("missing `new")));
+
+    // No `user` and no main avatar
+    let errors = parse_source("new \"T\"\njot \"hi\"\n", &json!({})).unwrap_err();
+    let error = errors.iter().find(|d| d.is_error()).unwrap();
+    assert_eq!((error.span.unwrap().line, error.span.unwrap().col), (1, 1));
+    assert!(error.help.as_deref().unwrap().contains("fur avatar"));
+
+    let errors = parse_source("new Untitled\n", &json!({"main": "me"})).unwrap_err();
+    assert!(errors[0].message.contains("quoted"));
+}
+
+#[test]
+fn fur_run_prints_compiler_style_diagnostics() {
+    let tmp = tempdir().unwrap();
+    init_store(tmp.path());
+    fs::write(tmp.path().join("bad.frs"), "new \"Bad\"\n\njot \"ok\"\n  oops here\n").unwrap();
+    fs::write(tmp.path().join("worse.frs"), "jot \"no title\"\n").unwrap();
+
+    fur_cmd(tmp.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "bad.frs"])
+        .assert()
+        .success()
+        .stderr(contains("warning: unrecognized line"))
+        .stderr(contains("--> bad.frs:4:3"))
+        .stderr(contains("4 |   oops here"))
+        .stderr(contains("  |   ^^^^^^^^^"));
+
+    fur_cmd(tmp.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "worse.frs"])
+        .assert()
+        .failure()
+        .stderr(contains("error: missing `new \"Title\"`"))
+        .stderr(contains("Could not run worse.frs: 1 error"));
+
+    // An unreadable file has no location to point at
+    fur_cmd(tmp.path())
+        .env("NO_COLOR", "1")
+        .args(["run", "missing.frs"])
+        .assert()
+        .failure()
+        .stderr(contains("error: cannot read missing.frs"))
+        .stderr(contains("-->").not())
+        .stderr(contains("|").not());
+}
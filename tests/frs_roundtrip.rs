@@ -0,0 +1,105 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+use fur_cli::commands::save::{run_save, SaveArgs};
+use fur_cli::frs::ast::{ScriptItem, Thread};
+use fur_cli::frs::parser::parse_frs;
+use fur_cli::frs::persist_frs;
+
+mod common;
+use common::{fur, init_store, init_store_with_avatars};
+
+/// Title, tags, avatars and the jot tree, without source line numbers
+/// (nor ids and timestamps, which a first import makes up).
+fn shape(thread: &Thread) -> Value {
+    fn strip(v: &mut Value) {
+        match v {
+            Value::Object(map) => {
+                for key in ["line", "id", "timestamp"] {
+                    map.remove(key);
+                }
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let mut messages = serde_json::to_value(
+        thread
+            .items
+            .iter()
+            .filter_map(|i| if let ScriptItem::Message(m) = i { Some(m) } else { None })
+            .collect::<Vec<_>>(),
+    )
+    .unwrap();
+    strip(&mut messages);
+    json!({
+        "title": thread.title,
+        "tags": thread.tags,
+        "avatars": thread.avatars,
+        "messages": messages,
+    })
+}
+
+/// Import `script` into a fresh store and save it back out.
+fn import_and_save(script: &Path, dir: &Path) -> String {
+    let store = init_store(dir);
+    let thread = parse_frs(script.to_str().unwrap(), &json!({})).unwrap();
+    persist_frs(&store, &thread).unwrap();
+    let out = dir.join("saved.frs");
+    run_save(&store, SaveArgs { out: Some(out.to_string_lossy().to_string()) }).unwrap();
+    fs::read_to_string(out).unwrap()
+}
+
+#[test]
+fn examples_survive_save_and_run() {
+    let mut checked = 0;
+    for entry in fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("frs") {
+            continue;
+        }
+        let original = parse_frs(path.to_str().unwrap(), &json!({})).unwrap();
+
+        let first = tempdir().unwrap();
+        let saved = import_and_save(&path, first.path());
+        let reparsed = parse_frs(first.path().join("saved.frs").to_str().unwrap(), &json!({})).unwrap();
+        assert!(reparsed.warnings.is_empty(), "{}: {:?}", path.display(), reparsed.warnings);
+        assert_eq!(shape(&original), shape(&reparsed), "{}", path.display());
+
+        // Saving the re-imported thread gives the same script
+        let second = tempdir().unwrap();
+        assert_eq!(saved, import_and_save(&first.path().join("saved.frs"), second.path()), "{}", path.display());
+        checked += 1;
+    }
+    assert!(checked >= 4);
+}
+
+#[test]
+fn cli_built_threads_round_trip() {
+    let a = tempdir().unwrap();
+    init_store_with_avatars(a.path(), r#"{"main":"me","me":"🦊","bot":"🤖"}"#);
+
+    fur(a.path(), &["new", "Odd \"Text\""]).success();
+    fur(a.path(), &["jot", "say \"hi\" \\o/"]).success();
+    fur(a.path(), &["jot", "bot", "line one\n  indented\n\n# not a comment\n", "--parent", "HEAD"]).success();
+    fur(a.path(), &["jot", "--img", "pic.png", "--text", "see\tthis", "--parent", "HEAD"]).success();
+    fur(a.path(), &["branch"]).success();
+    fur(a.path(), &["jot", "bot", ">>>\nstill text"]).success();
+    fur(a.path(), &["save", "--out", "a.frs"]).success();
+    let saved = fs::read_to_string(a.path().join("a.frs")).unwrap();
+    assert!(saved.contains("user = me\navatar bot = \"🤖\"\navatar me = \"🦊\""));
+    assert!(saved.contains("--attach \"pic.png\""));
+    assert!(saved.contains("reply {"));
+
+    let b = tempdir().unwrap();
+    init_store(b.path());
+    fs::copy(a.path().join("a.frs"), b.path().join("a.frs")).unwrap();
+    fur(b.path(), &["run", "a.frs"]).success();
+    fur(b.path(), &["save", "--out", "b.frs"]).success();
+    assert_eq!(saved, fs::read_to_string(b.path().join("b.frs")).unwrap());
+
+    let avatars: Value = serde_json::from_str(&fs::read_to_string(b.path().join(".fur/avatars.json")).unwrap()).unwrap();
+    assert_eq!(avatars["bot"], "🤖");
+}
@@ -0,0 +1,57 @@
+use serde_json::json;
+use fur_cli::frs::ast::ScriptItem;
+use fur_cli::frs::parser::parse_source;
+
+fn texts(script: &str) -> (Vec<String>, usize) {
+    let thread = parse_source(script, &json!({"main": "me"})).unwrap();
+    let texts = thread
+        .items
+        .iter()
+        .filter_map(|i| if let ScriptItem::Message(m) = i { m.text.clone() } else { None })
+        .collect();
+    (texts, thread.warnings.len())
+}
+
+#[test]
+fn raw_blocks_keep_text_verbatim() {
+    let script = r#"new "Blocks"
+
+jot me <<<
+    # Heading, not a comment
+    Some "quoted" words.
+
+    ```rust
+    fn main() {
+        println!("hi");
+    }
+    ```
+    >>>
+jot """
+  trailing line
+  """
+jot "after"
+"#;
+    let (texts, warnings) = texts(script);
+    assert_eq!(warnings, 0);
+    assert_eq!(
+        texts[0],
+        "# Heading, not a comment\nSome \"quoted\" words.\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```"
+    );
+    assert_eq!(texts[1], "trailing line");
+    assert_eq!(texts[2], "after");
+}
+
+#[test]
+fn quoted_strings_understand_escapes() {
+    let (texts, _) = texts("new \"T\"\njot \"say \\\"hi\\\"\\nthen\\ttab \\\\ and \\q\"\n");
+    assert_eq!(texts, ["say \"hi\"\nthen\ttab \\ and \\q"]);
+}
+
+#[test]
+fn unterminated_block_warns_and_runs_to_end() {
+    let thread = parse_source("new \"T\"\njot <<< extra\nline one\n  line two\n", &json!({"main": "me"})).unwrap();
+    let messages: Vec<&str> = thread.warnings.iter().map(|w| w.message.as_str()).collect();
+    assert_eq!(messages, ["unterminated text block", "text after `<<<` was ignored"]);
+    assert_eq!(thread.warnings[0].span.unwrap().line, 2);
+    assert!(matches!(&thread.items[0], ScriptItem::Message(m) if m.text.as_deref() == Some("line one\n  line two")));
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use std::time::Duration;
+use tempfile::tempdir;
+use fur_cli::commands::run::run_frs;
+use fur_cli::store::{StoredMessage, StoredThread};
+
+mod common;
+use common::{fur, init_store};
+
+#[test]
+fn gc_collects_only_unreachable_messages() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    let mut thread = StoredThread::new("Kept");
+    let root = StoredMessage::new("me", None);
+    let orphan = StoredMessage::new("me", None);
+    thread.messages.push(root.id.clone());
+    store.save_message(&root).unwrap();
+    store.save_message(&orphan).unwrap();
+    store.save_thread(&thread).unwrap();
+    let mut index = store.load_index().unwrap();
+    index.threads.push(thread.id.clone());
+    store.save_index(&index).unwrap();
+
+    // Inside the grace period nothing is touched
+    let report = store.gc(Duration::from_secs(3600), false).unwrap();
+    assert!(report.collected.is_empty());
+    assert_eq!(report.kept_recent, vec![orphan.id.clone()]);
+
+    // Dry run lists it but leaves the file
+    let report = store.gc(Duration::ZERO, true).unwrap();
+    assert_eq!(report.collected, vec![orphan.id.clone()]);
+    assert!(store.message_exists(&orphan.id));
+
+    store.gc(Duration::ZERO, false).unwrap();
+    assert!(!store.message_exists(&orphan.id));
+    assert!(store.message_exists(&root.id));
+}
+
+#[test]
+fn previews_leave_no_orphans() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    let script_path = tmp.path().join("preview.frs");
+    fs::write(&script_path, r#"
+        new "Preview Only"
+        user = me
+        jot "hello"
+        branch {
+            jot "nested"
+        }
+        tree
+        status
+    "#).unwrap();
+    run_frs(&store, script_path.to_str().unwrap()).unwrap();
+
+    assert_eq!(fs::read_dir(store.messages_dir()).unwrap().count(), 0);
+    assert_eq!(fs::read_dir(store.tmp_dir()).unwrap().count(), 0);
+}
+
+#[test]
+fn oversized_grace_is_a_parse_error() {
+    let tmp = tempdir().unwrap();
+    init_store(tmp.path());
+
+    fur(tmp.path(), &["gc", "--grace", "999999999999999999d"])
+        .failure()
+        .stderr(predicates::str::contains("duration too long"));
+}
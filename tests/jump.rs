@@ -0,0 +1,86 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+use fur_cli::store::FurStore;
+
+mod common;
+use common::{activate, attach, fur, init_store, message, save_thread};
+
+/// root ─ a ─ b
+///        └ branches: [c1, c2] | [d1]  (under b)
+///                     c1 ─ e ─ branches: [f] | [g]
+fn setup(tmp: &std::path::Path) -> FurStore {
+    let store = init_store(tmp);
+    let root = message(Some("root0000-0000"), None, "root0000");
+    let thread = save_thread(&store, "Jump", &[&root]);
+    let children = [
+        ("aaaa0000", "root0000", None),
+        ("bbbb0000", "aaaa0000", None),
+        ("cccc1111", "bbbb0000", Some(0)),
+        ("cccc2222", "bbbb0000", Some(0)),
+        ("dddd1111", "bbbb0000", Some(1)),
+        ("eeee0000", "cccc1111", None),
+        ("ffff0000", "eeee0000", Some(0)),
+        ("gggg0000", "eeee0000", Some(1)),
+    ];
+    for (id, parent, group) in children {
+        let m = message(Some(&format!("{}-0000", id)), Some(&format!("{}-0000", parent)), id);
+        attach(&store, &m, group);
+    }
+    activate(&store, &thread.id, Some("bbbb0000-0000"));
+    store
+}
+
+/// Run `fur jump <args>` and return the new current message's short id
+fn jump(store: &FurStore, tmp: &std::path::Path, args: &[&str]) -> String {
+    fur(tmp, &[&["jump"], args].concat()).success();
+    let current = store.load_index().unwrap().current_message.unwrap();
+    current[..8].to_string()
+}
+
+#[test]
+fn jump_walks_the_whole_tree() {
+    let tmp = tempdir().unwrap();
+    let store = setup(tmp.path());
+
+    assert_eq!(jump(&store, tmp.path(), &["--past", "1"]), "aaaa0000");
+    assert_eq!(jump(&store, tmp.path(), &["--child", "0"]), "bbbb0000");
+    assert_eq!(jump(&store, tmp.path(), &["--child", "1"]), "cccc2222");
+    assert_eq!(jump(&store, tmp.path(), &["--prev"]), "cccc1111");
+    assert_eq!(jump(&store, tmp.path(), &["--next"]), "cccc2222");
+    assert_eq!(jump(&store, tmp.path(), &["--next"]), "dddd1111");
+    // Nothing past the last sibling: stays put
+    assert_eq!(jump(&store, tmp.path(), &["--next"]), "dddd1111");
+    assert_eq!(jump(&store, tmp.path(), &["--past", "3"]), "root0000");
+    assert_eq!(jump(&store, tmp.path(), &["--leaf"]), "ffff0000");
+    assert_eq!(jump(&store, tmp.path(), &["--root"]), "root0000");
+    assert_eq!(jump(&store, tmp.path(), &["--branch", "2"]), "dddd1111");
+    assert_eq!(jump(&store, tmp.path(), &["--branch", "1.2"]), "gggg0000");
+    assert_eq!(jump(&store, tmp.path(), &["--id", "eeee"]), "eeee0000");
+
+    fur(tmp.path(), &["jump", "--branch", "3"]).failure();
+
+    // Misses are errors, and leave the cursor where it was
+    let elsewhere = message(Some("zzzz0000-0000"), None, "zzzz0000");
+    save_thread(&store, "Elsewhere", &[&elsewhere]);
+    fur(tmp.path(), &["jump", "--id", "zzzz"]).failure().stderr(contains("not in thread \"Jump\""));
+    fur(tmp.path(), &["jump", "--child", "9"]).failure().stderr(contains("No such child"));
+    fur(tmp.path(), &["jump"]).failure().stderr(contains("No jump argument"));
+    assert_eq!(store.load_index().unwrap().current_message.as_deref(), Some("eeee0000-0000"));
+}
+
+#[test]
+fn branch_labels_resolve_inside_nested_groups() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let script = std::fs::read_to_string("examples/dad_jokes.frs").unwrap() + "\nstore\n";
+    std::fs::write(tmp.path().join("jokes.frs"), script).unwrap();
+    fur(tmp.path(), &["run", "jokes.frs"]).success();
+
+    for (label, text) in [("1", "To get to the other side?"), ("1.1", "Not a dad, just broken."), ("1.2", "Yes, six kids."), ("2", "No idea, why?")] {
+        fur(tmp.path(), &["jump", "--branch", label])
+            .success()
+            .stdout(contains(format!("[Branch {}]", label)));
+        let current = store.load_index().unwrap().current_message.unwrap();
+        assert_eq!(store.load_message(&current).unwrap().text.as_deref(), Some(text));
+    }
+}
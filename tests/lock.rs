@@ -0,0 +1,49 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+use std::fs;
+use fur_cli::store::{FurStore, StoreError};
+
+mod common;
+use common::{activate, fur, init_store, save_thread};
+
+/// Helper: bootstrap a `.fur` directory with one active thread
+fn setup_fur(tmp: &std::path::Path) -> FurStore {
+    let store = init_store(tmp);
+    let thread = save_thread(&store, "Locked", &[]);
+    activate(&store, &thread.id, None);
+    store
+}
+
+#[test]
+fn second_lock_is_refused() {
+    let tmp = tempdir().unwrap();
+    let store = setup_fur(tmp.path());
+
+    let guard = store.lock().unwrap();
+    assert!(matches!(store.lock(), Err(StoreError::Locked(_))));
+    drop(guard);
+    assert!(store.lock().is_ok());
+}
+
+#[test]
+fn jot_fails_cleanly_while_locked() {
+    let tmp = tempdir().unwrap();
+    let store = setup_fur(tmp.path());
+
+    let guard = store.lock().unwrap();
+    fur(tmp.path(), &["jot", "blocked"])
+        .failure()
+        .stderr(contains("Another `fur` process"));
+    drop(guard);
+
+    // Once released, the jot goes through and leaves no temp files behind
+    fur(tmp.path(), &["jot", "allowed"]).success();
+
+    let leftovers: Vec<_> = fs::read_dir(tmp.path().join(".fur/messages"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+    assert_eq!(fs::read_dir(tmp.path().join(".fur/messages")).unwrap().count(), 1);
+}
@@ -0,0 +1,41 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+
+mod common;
+use common::{fur, init_store};
+
+#[test]
+fn marks_are_set_listed_shown_and_jumped_to() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "First"]).success();
+    fur(tmp.path(), &["jot", "question"]).success();
+    fur(tmp.path(), &["jot", "answer", "--parent", "HEAD"]).success();
+    fur(tmp.path(), &["mark", "todo", "HEAD~1"]).success().stdout(contains("question"));
+    fur(tmp.path(), &["mark", "home", "--global"]).success();
+
+    let index = store.load_index().unwrap();
+    let first = store.active_thread(&index).unwrap();
+    let answer = index.current_message.clone().unwrap();
+    assert_eq!(first.marks.get("todo"), Some(&first.messages[0]));
+    assert_eq!(index.marks.get("home"), Some(&answer));
+
+    fur(tmp.path(), &["marks"]).success().stdout(contains("todo")).stdout(contains("global"));
+    fur(tmp.path(), &["tree"]).success().stdout(contains("🔖 todo")).stdout(contains("🔖 home"));
+    fur(tmp.path(), &["timeline"]).success().stdout(contains("🔖 todo"));
+
+    fur(tmp.path(), &["jump", "--mark", "todo"]).success();
+    assert_eq!(store.load_index().unwrap().current_message, Some(first.messages[0].clone()));
+
+    // A global mark switches back to the thread holding it
+    fur(tmp.path(), &["new", "Second"]).success();
+    fur(tmp.path(), &["jump", "--mark", "todo"]).failure().stderr(contains("No mark named"));
+    fur(tmp.path(), &["jump", "--mark", "home"]).success();
+    let index = store.load_index().unwrap();
+    assert_eq!(index.active_thread, Some(first.id.clone()));
+    assert_eq!(index.current_message, Some(answer));
+
+    fur(tmp.path(), &["mark", "todo", "--delete"]).success();
+    assert!(store.load_thread(&first.id).unwrap().marks.is_empty());
+}
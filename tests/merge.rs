@@ -0,0 +1,78 @@
+use tempfile::tempdir;
+
+mod common;
+use common::{init_store, message, reply, save_thread};
+
+#[test]
+fn merge_fork_back_grafts_only_new_messages() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let root = message(None, None, "root");
+    let original = save_thread(&store, "Original", &[&root]);
+    let a = reply(&store, &root.id, "a");
+
+    let (fork, copies) = store.fork_thread(&original.id, "Fork").unwrap();
+    let b = reply(&store, &copies[&a], "b");
+    reply(&store, &b, "b1");
+
+    let report = store.merge_thread(&fork.id, &original.id, None, None).unwrap();
+    assert_eq!(report.skipped, 2);
+    assert_eq!(report.copied, 2);
+    assert_eq!(report.grafted.len(), 1);
+
+    // b lands under the original a, as a new branch group of copies
+    let (anchor, ids) = &report.grafted[0];
+    assert_eq!(anchor.as_deref(), Some(a.as_str()));
+    let a_msg = store.load_message(&a).unwrap();
+    assert_eq!(a_msg.branches, vec![ids.clone()]);
+    let b_copy = store.load_message(&ids[0]).unwrap();
+    assert_eq!(b_copy.copy_of.as_deref(), Some(b.as_str()));
+    assert_eq!(b_copy.text.as_deref(), Some("b"));
+
+    // Merging again finds nothing new
+    let again = store.merge_thread(&fork.id, &original.id, None, None).unwrap();
+    assert!(again.grafted.is_empty());
+    assert!(store.check().unwrap().is_empty());
+}
+
+#[test]
+fn merge_unrelated_thread_or_subtree_under_a_message() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let root = message(None, None, "root");
+    let target = save_thread(&store, "Target", &[&root]);
+    let x = message(None, None, "x");
+    let y = message(None, None, "y");
+    let other = save_thread(&store, "Other", &[&x, &y]);
+    let y1 = reply(&store, &y.id, "y1");
+
+    let report = store.merge_thread(&other.id, &target.id, None, Some(&root.id)).unwrap();
+    assert_eq!(report.copied, 3);
+    let r = store.load_message(&root.id).unwrap();
+    assert_eq!(r.branches.len(), 1);
+    assert_eq!(r.branches[0].len(), 2);
+
+    // Only y1's subtree, as new roots of a fresh thread
+    let empty = save_thread(&store, "Empty", &[]);
+    let report = store.merge_thread(&other.id, &empty.id, Some(&y1), None).unwrap();
+    assert_eq!(report.copied, 1);
+    assert_eq!(store.load_thread(&empty.id).unwrap().messages, report.grafted[0].1);
+    assert!(store.check().unwrap().is_empty());
+}
+
+#[test]
+fn merge_from_must_be_in_the_source_thread() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let root = message(None, None, "root");
+    let target = save_thread(&store, "Target", &[&root]);
+    let x = message(None, None, "x");
+    let source = save_thread(&store, "Source", &[&x]);
+    let elsewhere = message(None, None, "elsewhere");
+    save_thread(&store, "Elsewhere", &[&elsewhere]);
+
+    let err = store.merge_thread(&source.id, &target.id, Some(&elsewhere.id), None).unwrap_err();
+    assert!(err.to_string().contains("\"Source\""));
+    assert_eq!(store.load_message(&root.id).unwrap().children.len(), 0);
+    assert_eq!(store.load_thread(&target.id).unwrap().messages, vec![root.id.clone()]);
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use tempfile::tempdir;
+use serde_json::Value;
+use fur_cli::store::{FurStore, StoreError, SCHEMA_VERSION};
+
+/// Helper: a pre-versioning store with the quirks older builds produced
+fn setup_old_fur(tmp: &std::path::Path) -> std::path::PathBuf {
+    let fur_dir = tmp.join(".fur");
+    fs::create_dir_all(fur_dir.join("threads")).unwrap();
+    fs::create_dir_all(fur_dir.join("messages")).unwrap();
+    fs::write(fur_dir.join("index.json"), r#"{
+        "threads": ["t1"],
+        "active_thread": "t1"
+    }"#).unwrap();
+    // forks used to be written without tags
+    fs::write(fur_dir.join("threads/t1.json"), r#"{
+        "id": "t1", "title": "Old", "messages": ["m1"]
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m1.json"), r#"{
+        "id": "m1", "avatar": "me", "text": "root", "markdown": null, "parent": null,
+        "children": [], "branches": [["m2"]], "timestamp": "2025-01-01T00:00:00Z"
+    }"#).unwrap();
+    fs::write(fur_dir.join("messages/m2.json"), r#"{
+        "id": "m2", "avatar": "me", "text": "child", "parent": "m1",
+        "timestamp": "2025-01-01T00:00:00Z"
+    }"#).unwrap();
+    fs::write(fur_dir.join("avatars.json"), r#"{"main":"me"}"#).unwrap();
+    fur_dir
+}
+
+fn read(path: std::path::PathBuf) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn dry_run_reports_without_writing() {
+    let tmp = tempdir().unwrap();
+    let fur_dir = setup_old_fur(tmp.path());
+    let before = fs::read_to_string(fur_dir.join("messages/m1.json")).unwrap();
+
+    let store = FurStore::open_unmigrated(&fur_dir).unwrap();
+    let report = store.migrate(true).unwrap();
+
+    assert_eq!(report.from, "0.1");
+    assert_eq!(report.to, SCHEMA_VERSION);
+    assert_eq!(report.steps.len(), 2);
+    assert!(report.changed.contains(&fur_dir.join("messages/m1.json")));
+    assert_eq!(fs::read_to_string(fur_dir.join("messages/m1.json")).unwrap(), before);
+    assert_eq!(store.schema_version().unwrap(), "0.1");
+}
+
+#[test]
+fn open_upgrades_every_file_kind() {
+    let tmp = tempdir().unwrap();
+    let fur_dir = setup_old_fur(tmp.path());
+
+    let store = FurStore::open(&fur_dir).unwrap();
+    assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+    assert!(store.pending_migrations().unwrap().is_empty());
+
+    let m1 = read(fur_dir.join("messages/m1.json"));
+    assert_eq!(m1["children"], serde_json::json!(["m2"]));
+    assert!(m1.get("markdown").is_none());
+    assert!(read(fur_dir.join("messages/m2.json"))["branches"].is_array());
+    assert!(read(fur_dir.join("threads/t1.json"))["tags"].is_array());
+    assert_eq!(read(fur_dir.join("avatars.json"))["me"], "🦊");
+    assert!(read(fur_dir.join("index.json"))["current_message"].is_null());
+
+    // Running again is a no-op
+    assert!(store.migrate(false).unwrap().is_noop());
+}
+
+#[test]
+fn newer_schema_is_refused() {
+    let tmp = tempdir().unwrap();
+    let fur_dir = setup_old_fur(tmp.path());
+    fs::write(fur_dir.join("index.json"), r#"{"threads": [], "schema_version": "9.9"}"#).unwrap();
+
+    assert!(matches!(FurStore::open(&fur_dir), Err(StoreError::UnsupportedSchema(v)) if v == "9.9"));
+}
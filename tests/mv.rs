@@ -0,0 +1,73 @@
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, StoreError, StoredThread};
+
+mod common;
+use common::{activate, init_store, message, reply, save_thread};
+
+/// Helper: a thread of three root jots (the way plain `jot` leaves them),
+/// the second one with a reply. Returns the thread and [r1, r2, r2a, r3].
+fn setup_roots(store: &FurStore, title: &str) -> (StoredThread, Vec<String>) {
+    let (r1, r2, r3) = (message(None, None, "r1"), message(None, None, "r2"), message(None, None, "r3"));
+    let thread = save_thread(store, title, &[&r1, &r2, &r3]);
+    let r2a = reply(store, &r2.id, "r2a");
+    activate(store, &thread.id, None);
+    (thread, vec![r1.id, r2.id, r2a, r3.id])
+}
+
+#[test]
+fn mv_reparents_within_thread_and_refuses_cycles() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let (thread, ids) = setup_roots(&store, "Main");
+    let (r1, r2, r2a, r3) = (&ids[0], &ids[1], &ids[2], &ids[3]);
+
+    // r2 (with its reply) becomes a child of r1
+    let report = store.move_message(r2, Some(r1), None).unwrap();
+    assert_eq!(report.moved, 2);
+    assert_eq!(store.load_thread(&thread.id).unwrap().messages, vec![r1.clone(), r3.clone()]);
+    assert_eq!(store.load_message(r1).unwrap().children, vec![r2.clone()]);
+    assert_eq!(store.load_message(r2).unwrap().parent.as_ref(), Some(r1));
+
+    // Can't move r1 under its own grandchild
+    assert!(matches!(
+        store.move_message(r1, Some(r2a), None),
+        Err(StoreError::WouldCycle { .. })
+    ));
+
+    // A parent with branch groups gets the newcomer as its own group
+    let mut p = store.load_message(r1).unwrap();
+    p.branches = vec![vec![r2.clone()]];
+    store.save_message(&p).unwrap();
+    store.move_message(r3, Some(r1), None).unwrap();
+    let p = store.load_message(r1).unwrap();
+    assert_eq!(p.children, vec![r2.clone(), r3.clone()]);
+    assert_eq!(p.branches, vec![vec![r2.clone()], vec![r3.clone()]]);
+
+    assert!(store.check().unwrap().is_empty());
+}
+
+#[test]
+fn mv_carries_subtree_to_another_thread() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let (source, ids) = setup_roots(&store, "Source");
+    let (target, target_ids) = setup_roots(&store, "Target");
+
+    // As a root of the other thread
+    store.move_message(&ids[1], None, Some(&target.id)).unwrap();
+    assert_eq!(store.load_thread(&source.id).unwrap().messages, vec![ids[0].clone(), ids[3].clone()]);
+    assert_eq!(store.load_thread(&target.id).unwrap().messages.last(), Some(&ids[1]));
+
+    // Under a message of the other thread; the thread is inferred
+    store.move_message(&ids[0], Some(&target_ids[0]), None).unwrap();
+    assert_eq!(store.load_message(&target_ids[0]).unwrap().children, vec![ids[0].clone()]);
+
+    // --thread must agree with --parent
+    assert!(matches!(
+        store.move_message(&ids[3], Some(&target_ids[0]), Some(&source.id)),
+        Err(StoreError::Invalid(_))
+    ));
+
+    assert_eq!(store.load_tree(&store.load_thread(&target.id).unwrap()).len(), 7);
+    assert!(store.check().unwrap().is_empty());
+}
@@ -0,0 +1,36 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+
+mod common;
+use common::{fur, init_store};
+
+#[test]
+fn reflog_records_moves_and_jump_returns_to_them() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Deep"]).success();
+    fur(tmp.path(), &["jot", "one"]).success();
+    fur(tmp.path(), &["jot", "two", "--parent", "HEAD"]).success();
+    fur(tmp.path(), &["jot", "three", "--parent", "HEAD"]).success();
+    let deep = store.load_index().unwrap();
+    fur(tmp.path(), &["jump", "--root"]).success();
+    fur(tmp.path(), &["new", "Other"]).success();
+
+    let log = store.load_reflog().unwrap();
+    let actions: Vec<&str> = log.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, ["new", "jump", "jot", "jot", "jot", "new"]);
+    assert_eq!(log[2].after.message, deep.current_message);
+    assert_eq!(log[1].before.message, deep.current_message);
+
+    fur(tmp.path(), &["reflog"]).success().stdout(contains("three")).stdout(contains("jump"));
+
+    // Back to where the last jot left us, across threads
+    fur(tmp.path(), &["jump", "--reflog", "2"]).success();
+    let index = store.load_index().unwrap();
+    assert_eq!(index.active_thread, deep.active_thread);
+    assert_eq!(index.current_message, deep.current_message);
+    assert_eq!(store.load_reflog().unwrap()[0].action, "jump");
+
+    fur(tmp.path(), &["jump", "--reflog", "99"]).failure().stderr(contains("No reflog entry"));
+}
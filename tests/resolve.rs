@@ -0,0 +1,64 @@
+use tempfile::tempdir;
+use fur_cli::store::StoreError;
+
+mod common;
+use common::{activate, attach, init_store, message, save_thread};
+
+#[test]
+fn resolves_prefixes_and_symbolic_names() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    // Chosen ids so prefixes are predictable
+    let root = message(Some("aaaa0000-root"), None, "the root");
+    let mid = message(Some("abcd1111-mid"), Some("aaaa0000-root"), "middle\nsecond line");
+    let tip = message(Some("abcd2222-tip"), Some("abcd1111-mid"), "the tip");
+    let thread = save_thread(&store, "Resolve", &[&root]);
+    attach(&store, &mid, None);
+    attach(&store, &tip, None);
+    activate(&store, &thread.id, Some(&tip.id));
+
+    assert_eq!(store.resolve_message("abcd2").unwrap(), tip.id);
+    assert_eq!(store.resolve_message(&root.id).unwrap(), root.id);
+    assert!(matches!(store.resolve_message("zzz"), Err(StoreError::MessageNotFound(_))));
+
+    // Ambiguity lists avatar + first line for each candidate
+    let err = store.resolve_message("abcd").unwrap_err();
+    let shown = err.to_string();
+    assert!(shown.contains("abcd1111  [me] middle"));
+    assert!(!shown.contains("second line"));
+    assert!(shown.contains("abcd2222  [me] the tip"));
+
+    assert_eq!(store.resolve_message("HEAD").unwrap(), tip.id);
+    assert_eq!(store.resolve_message("HEAD~1").unwrap(), mid.id);
+    assert_eq!(store.resolve_message("HEAD~2").unwrap(), root.id);
+    assert_eq!(store.resolve_message("root").unwrap(), root.id);
+    assert!(matches!(store.resolve_message("HEAD~3"), Err(StoreError::Invalid(_))));
+
+    let index = store.load_index().unwrap();
+    assert_eq!(store.resolve_thread(&index, &thread.id[..6]).unwrap(), thread.id);
+    assert_eq!(store.resolve_thread(&index, "HEAD").unwrap(), thread.id);
+}
+
+#[test]
+fn prefixes_resolve_within_the_active_thread() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    let here = message(Some("abcd2222-here"), None, "in the active thread");
+    let there = message(Some("abcd2299-there"), None, "in another thread");
+    let other = save_thread(&store, "Other", &[&there]);
+    let active = save_thread(&store, "Active", &[&here]);
+    activate(&store, &active.id, Some(&here.id));
+
+    // Unique here even though the store has two matches
+    assert_eq!(store.resolve_message("abcd2").unwrap(), here.id);
+    assert!(matches!(store.resolve_any_message("abcd2"), Err(StoreError::AmbiguousId { .. })));
+
+    // Another thread's message is named as such, not silently accepted
+    let err = store.resolve_message("abcd229").unwrap_err();
+    assert!(err.to_string().contains("not in thread \"Active\""), "{}", err);
+    assert_eq!(store.resolve_any_message("abcd229").unwrap(), there.id);
+    assert_eq!(store.resolve_message_in("abcd229", &other).unwrap(), there.id);
+    assert!(matches!(store.resolve_message("zzz"), Err(StoreError::MessageNotFound(_))));
+}
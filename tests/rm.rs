@@ -0,0 +1,62 @@
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, StoreError, StoredThread};
+
+mod common;
+use common::{activate, attach, init_store, message, reply, save_thread};
+
+/// Helper: root → [branch a → a1] + plain child b, with a1 as current
+fn setup_tree(store: &FurStore) -> (StoredThread, Vec<String>) {
+    let root = message(None, None, "root");
+    let thread = save_thread(store, "Prune", &[&root]);
+    let a = message(None, Some(&root.id), "a");
+    attach(store, &a, Some(0));
+    let a1 = reply(store, &a.id, "a1");
+    let b = reply(store, &root.id, "b");
+    activate(store, &thread.id, Some(&a1));
+
+    (thread, vec![root.id, a.id, a1, b])
+}
+
+#[test]
+fn rm_and_restore_subtree() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let (_, ids) = setup_tree(&store);
+    let (root, a, a1, b) = (&ids[0], &ids[1], &ids[2], &ids[3]);
+
+    // Refuses to orphan replies without --recursive
+    assert!(matches!(store.remove_message(a, false), Err(StoreError::HasChildren { count: 1, .. })));
+
+    let entry = store.remove_message(a, true).unwrap();
+    assert_eq!(entry.messages, vec![a.clone(), a1.clone()]);
+    assert!(!store.message_exists(a) && !store.message_exists(a1));
+
+    let r = store.load_message(root).unwrap();
+    assert_eq!(r.children, vec![b.clone()]);
+    assert!(r.branches.is_empty());
+    assert_eq!(store.load_index().unwrap().current_message.as_ref(), Some(root));
+    assert!(store.check().unwrap().is_empty());
+
+    store.restore(&a[..8]).unwrap();
+    let r = store.load_message(root).unwrap();
+    assert_eq!(r.children, vec![a.clone(), b.clone()]);
+    assert_eq!(r.branches, vec![vec![a.clone()]]);
+    assert!(store.message_exists(a1));
+    assert!(store.list_trash().unwrap().is_empty());
+    assert!(store.check().unwrap().is_empty());
+}
+
+#[test]
+fn rm_root_message() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    let (thread, ids) = setup_tree(&store);
+
+    store.remove_message(&ids[0], true).unwrap();
+    assert!(store.load_thread(&thread.id).unwrap().messages.is_empty());
+    assert_eq!(store.load_index().unwrap().current_message, None);
+
+    store.restore(&ids[0]).unwrap();
+    assert_eq!(store.load_thread(&thread.id).unwrap().messages, vec![ids[0].clone()]);
+    assert_eq!(store.load_tree(&thread).len(), 4);
+}
@@ -0,0 +1,126 @@
+use predicates::str::contains;
+use std::fs;
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, MarkScope, StoredMessage};
+
+mod common;
+use common::{fur, init_store};
+
+const V1: &str = r#"new "Plan"
+user = me
+
+jot "one"
+branch {
+  jot bot "two"
+  jot "three"
+}
+jot "four"
+store
+"#;
+
+const V2: &str = r#"new "Plan"
+user = me
+
+jot "one"
+branch {
+  jot bot "two!"
+  jot bot "five"
+}
+jot "four"
+reply {
+  jot "six"
+}
+store
+"#;
+
+/// (text, id) of every message in the active thread.
+fn messages(store: &FurStore) -> Vec<(String, String)> {
+    let index = store.load_index().unwrap();
+    let thread = store.active_thread(&index).unwrap();
+    let mut out: Vec<(String, String)> = store
+        .load_tree(&thread)
+        .into_values()
+        .map(|m: StoredMessage| (m.text.unwrap_or_default(), m.id))
+        .collect();
+    out.sort();
+    out
+}
+
+fn id_of(messages: &[(String, String)], text: &str) -> String {
+    messages.iter().find(|(t, _)| t == text).unwrap().1.clone()
+}
+
+#[test]
+fn update_keeps_ids_and_reports_changes() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    fs::write(tmp.path().join("plan.frs"), V1).unwrap();
+    fur(tmp.path(), &["run", "plan.frs"]).success();
+
+    let before = messages(&store);
+    let two = id_of(&before, "two");
+    store.set_mark("keep", &two, MarkScope::Thread).unwrap();
+
+    fs::write(tmp.path().join("plan.frs"), V2).unwrap();
+    fur(tmp.path(), &["run", "--update", "plan.frs"]).success()
+        .stdout(contains("~ "))
+        .stdout(contains("[bot] two!"))
+        .stdout(contains("[me] three"))
+        .stdout(contains("2 added, 1 changed, 0 moved, 1 removed, 2 unchanged"));
+
+    let after = messages(&store);
+    assert_eq!(after.len(), 5);
+    assert_eq!(id_of(&after, "one"), id_of(&before, "one"));
+    assert_eq!(id_of(&after, "four"), id_of(&before, "four"));
+    assert_eq!(id_of(&after, "two!"), two);
+    assert!(!store.message_exists(&id_of(&before, "three")));
+
+    // The edit kept the old text, and the bookmark still points at it
+    let edited = store.load_message(&two).unwrap();
+    assert_eq!(edited.revisions[0].text.as_deref(), Some("two"));
+    fur(tmp.path(), &["jump", "--mark", "keep"]).success();
+    assert_eq!(store.load_index().unwrap().threads.len(), 1);
+
+    // Running the same script again changes nothing
+    fur(tmp.path(), &["run", "--update", "plan.frs"]).success()
+        .stdout(contains("Nothing changed."))
+        .stdout(contains("0 added, 0 changed, 0 moved, 0 removed, 5 unchanged"));
+    assert_eq!(messages(&store), after);
+
+    // The no-op run recorded nothing; the update is one undoable operation
+    fur(tmp.path(), &["undo"]).success();
+    assert_eq!(messages(&store), before);
+}
+
+#[test]
+fn update_clears_only_references_to_removed_messages() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+    fs::write(tmp.path().join("plan.frs"), V1).unwrap();
+    fur(tmp.path(), &["run", "plan.frs"]).success();
+    let three = id_of(&messages(&store), "three");
+    store.set_mark("gone", &three, MarkScope::Global).unwrap();
+    let mut index = store.load_index().unwrap();
+    index.marks.insert("stale".to_string(), "deadbeef".to_string());
+    store.save_index(&index).unwrap();
+
+    // Another thread pointing into the part the script drops
+    fur(tmp.path(), &["new", "Other"]).success();
+    let index = store.load_index().unwrap();
+    let mut other = store.active_thread(&index).unwrap();
+    other.marks.insert("there".to_string(), three.clone());
+    other.cursor = Some(three.clone());
+    store.save_thread(&other).unwrap();
+
+    fs::write(tmp.path().join("plan.frs"), V2).unwrap();
+    fur(tmp.path(), &["run", "--update", "plan.frs"]).success();
+    assert!(!store.message_exists(&three));
+
+    // An unrelated dangling mark is left for `fur doctor`
+    let index = store.load_index().unwrap();
+    assert!(!index.marks.contains_key("gone"));
+    assert_eq!(index.marks.get("stale").map(String::as_str), Some("deadbeef"));
+    let other = store.load_thread(&other.id).unwrap();
+    assert!(other.marks.is_empty());
+    assert_eq!(other.cursor, None);
+}
@@ -0,0 +1,40 @@
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, StoredMessage, StoredThread};
+
+#[test]
+fn store_round_trips_typed_files() {
+    let tmp = tempdir().unwrap();
+    let store = FurStore::init(tmp.path().join(".fur")).unwrap();
+
+    // Thread with one root and one reply
+    let mut thread = StoredThread::new("Typed Store");
+    let mut root = StoredMessage::new("me", None);
+    root.text = Some("root".to_string());
+    let mut reply = StoredMessage::new("ai", Some(root.id.clone()));
+    reply.text = Some("reply".to_string());
+    root.children.push(reply.id.clone());
+    thread.messages.push(root.id.clone());
+
+    store.save_message(&root).unwrap();
+    store.save_message(&reply).unwrap();
+    store.save_thread(&thread).unwrap();
+
+    let mut index = store.load_index().unwrap();
+    index.threads.push(thread.id.clone());
+    index.active_thread = Some(thread.id.clone());
+    store.save_index(&index).unwrap();
+
+    // Reopen and read everything back
+    let store = FurStore::open(tmp.path().join(".fur")).unwrap();
+    let index = store.load_index().unwrap();
+    let loaded = store.active_thread(&index).unwrap();
+    assert_eq!(loaded.title, "Typed Store");
+
+    let tree = store.load_tree(&loaded);
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree[&reply.id].parent.as_deref(), Some(root.id.as_str()));
+    assert_eq!(store.subtree_ids(&root.id), vec![root.id.clone(), reply.id.clone()]);
+
+    assert!(store.load_message("missing").is_err());
+    assert!(FurStore::open(tmp.path().join("nowhere")).is_err());
+}
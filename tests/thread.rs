@@ -0,0 +1,72 @@
+use predicates::prelude::*;
+use tempfile::tempdir;
+use fur_cli::store::{FurStore, StoredThread};
+
+mod common;
+use common::{fur, init_store};
+
+/// Helper: store with threads "Alpha" and "Beta" (Beta active, one jot each)
+fn setup_threads(tmp: &std::path::Path) -> (FurStore, String, String) {
+    let store = init_store(tmp);
+    fur(tmp, &["new", "Alpha"]).success();
+    fur(tmp, &["jot", "alpha note"]).success();
+    fur(tmp, &["new", "Beta"]).success();
+    fur(tmp, &["jot", "beta note"]).success();
+    let index = store.load_index().unwrap();
+    (store, index.threads[0].clone(), index.threads[1].clone())
+}
+
+#[test]
+fn rename_tag_and_archive() {
+    let tmp = tempdir().unwrap();
+    let (store, alpha, beta) = setup_threads(tmp.path());
+
+    fur(tmp.path(), &["thread", "rename", "Gamma"]).success();
+    assert_eq!(store.load_thread(&beta).unwrap().title, "Gamma");
+
+    fur(tmp.path(), &["thread", "tag", "add", "work", "#ideas", "-t", &alpha[..6]]).success();
+    fur(tmp.path(), &["thread", "tag", "rm", "work", "-t", &alpha[..6]]).success();
+    assert_eq!(store.load_thread(&alpha).unwrap().tags, vec!["ideas".to_string()]);
+
+    fur(tmp.path(), &["thread", "archive", "-t", &alpha]).success();
+    fur(tmp.path(), &["thread"])
+        .success()
+        .stdout(predicate::str::contains("Alpha").not())
+        .stdout(predicate::str::contains("1 archived"));
+    fur(tmp.path(), &["thread", "--all"])
+        .success()
+        .stdout(predicate::str::contains("Alpha [archived]"));
+
+    fur(tmp.path(), &["thread", "unarchive", "-t", &alpha]).success();
+    assert!(!store.load_thread(&alpha).unwrap().archived);
+
+    // Switching by prefix still works alongside the subcommands
+    fur(tmp.path(), &["thread", &alpha[..8]]).success();
+    assert_eq!(store.load_index().unwrap().active_thread, Some(alpha));
+}
+
+#[test]
+fn rm_deletes_thread_but_keeps_shared_messages() {
+    let tmp = tempdir().unwrap();
+    let (store, alpha, beta) = setup_threads(tmp.path());
+
+    // An old-style fork of Beta that shares Beta's message files
+    let alpha_msgs = store.load_thread(&alpha).unwrap().messages;
+    let beta_msgs = store.load_thread(&beta).unwrap().messages;
+    let mut legacy = StoredThread::new("Legacy fork");
+    legacy.messages = beta_msgs.clone();
+    store.save_thread(&legacy).unwrap();
+    let mut index = store.load_index().unwrap();
+    index.threads.push(legacy.id.clone());
+    store.save_index(&index).unwrap();
+
+    fur(tmp.path(), &["thread", "rm", &alpha[..8], "-y"]).success();
+    fur(tmp.path(), &["thread", "rm", &beta[..8], "-y"]).success();
+
+    let index = store.load_index().unwrap();
+    assert_eq!(index.threads.len(), 1);
+    assert!(!store.thread_path(&alpha).exists());
+    assert!(!store.message_exists(&alpha_msgs[0]));
+    assert!(store.message_exists(&beta_msgs[0]));
+    assert!(store.check().unwrap().is_empty());
+}
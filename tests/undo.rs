@@ -0,0 +1,170 @@
+use predicates::str::contains;
+use std::fs;
+use tempfile::tempdir;
+use fur_cli::store::FurStore;
+
+mod common;
+use common::{fur, fur_cmd, init_store};
+
+/// Every file under `.fur/` that undo must restore, with its content
+fn snapshot(store: &FurStore) -> Vec<(String, String)> {
+    let mut out = vec![("index.json".to_string(), fs::read_to_string(store.index_path()).unwrap())];
+    for dir in [store.threads_dir(), store.messages_dir()] {
+        let mut files: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        files.sort();
+        for f in files {
+            out.push((f.display().to_string(), fs::read_to_string(&f).unwrap()));
+        }
+    }
+    out
+}
+
+#[test]
+fn undo_and_redo_restore_exact_files() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Undo"]).success();
+    fur(tmp.path(), &["jot", "keep"]).success();
+    let before_jot = snapshot(&store);
+
+    fur(tmp.path(), &["jot", "oops", "--parent", "HEAD"]).success();
+    let after_jot = snapshot(&store);
+    fur(tmp.path(), &["undo"]).success().stdout(contains("Undid jot \"oops\""));
+    assert_eq!(snapshot(&store), before_jot);
+    fur(tmp.path(), &["redo"]).success();
+    assert_eq!(snapshot(&store), after_jot);
+
+    // rm, rename: undone newest first
+    fur(tmp.path(), &["rm", "HEAD"]).success();
+    fur(tmp.path(), &["thread", "rename", "Renamed"]).success();
+    fur(tmp.path(), &["undo"]).success().stdout(contains("thread rename"));
+    fur(tmp.path(), &["undo"]).success().stdout(contains("rm"));
+    assert_eq!(snapshot(&store), after_jot);
+
+    // A new operation drops the redo history
+    fur(tmp.path(), &["jot", "again"]).success();
+    fur(tmp.path(), &["redo"]).failure().stderr(contains("Nothing to redo"));
+
+    // A file edited since the operation blocks undo unless --force
+    let index = store.load_index().unwrap();
+    let again = index.current_message.clone().unwrap();
+    let path = store.message_path(&again);
+    fs::write(&path, fs::read_to_string(&path).unwrap().replace("again", "edited")).unwrap();
+    fur(tmp.path(), &["undo"]).failure().stderr(contains("--force"));
+    fur(tmp.path(), &["undo", "--force"]).success();
+    assert!(!store.message_exists(&again));
+}
+
+#[test]
+fn undo_reverses_an_frs_overwrite() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    let script = tmp.path().join("notes.frs");
+    fs::write(&script, "new \"Notes\"\nuser = me\njot \"first draft\"\nstore\n").unwrap();
+    fur(tmp.path(), &["run", "notes.frs"]).success();
+    let original = snapshot(&store);
+
+    fs::write(&script, "new \"Notes\"\nuser = me\njot \"second draft\"\nstore\n").unwrap();
+    fur_cmd(tmp.path())
+        .args(["run", "notes.frs"])
+        .write_stdin("y\n")
+        .assert()
+        .success();
+    assert_ne!(snapshot(&store), original);
+
+    fur(tmp.path(), &["undo"]).success().stdout(contains("run"));
+    assert_eq!(snapshot(&store), original);
+}
+
+#[test]
+fn undo_keeps_threads_created_afterwards() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "A"]).success();
+    fur(tmp.path(), &["jot", "one"]).success();
+    fur(tmp.path(), &["jot", "two", "--parent", "HEAD"]).success();
+    let index = store.load_index().unwrap();
+    let (a, two) = (index.active_thread.unwrap(), index.current_message.unwrap());
+    fur(tmp.path(), &["mark", "-g", "later", "HEAD~1"]).success();
+
+    // Switching away writes A's cursor; neither blocks a plain undo
+    fur(tmp.path(), &["new", "B"]).success();
+    fur(tmp.path(), &["jot", "b1"]).success();
+    fur(tmp.path(), &["undo"]).success().stdout(contains("b1"));
+    fur(tmp.path(), &["undo"]).success().stdout(contains("two"));
+
+    let index = store.load_index().unwrap();
+    assert_eq!(index.threads.len(), 2);
+    assert_ne!(index.active_thread.as_deref(), Some(a.as_str()));
+    assert!(index.marks.contains_key("later"));
+    fur(tmp.path(), &["thread"]).success().stdout(contains("A")).stdout(contains("B"));
+
+    // A's saved cursor followed the undo instead of dangling
+    assert!(!store.message_exists(&two));
+    let cursor = store.load_thread(&a).unwrap().cursor.unwrap();
+    assert!(store.message_exists(&cursor));
+
+    fur(tmp.path(), &["redo"]).success();
+    fur(tmp.path(), &["redo"]).success();
+    assert!(store.message_exists(&two));
+    assert_eq!(store.load_index().unwrap().threads.len(), 2);
+
+    // An operation that wrote a thread file, then a switch that only moved
+    // that thread's cursor
+    fur(tmp.path(), &["thread", "rename", "B2"]).success();
+    fur(tmp.path(), &["jump", "--root"]).success();
+    fur(tmp.path(), &["new", "C"]).success();
+    fur(tmp.path(), &["undo"]).success().stdout(contains("thread rename"));
+    assert_eq!(store.load_index().unwrap().threads.len(), 3);
+}
+
+#[test]
+fn undo_of_rm_leaves_no_empty_trash_entry() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Trash"]).success();
+    fur(tmp.path(), &["jot", "keep"]).success();
+    fur(tmp.path(), &["jot", "drop"]).success();
+    let drop = store.load_index().unwrap().current_message.unwrap();
+    fur(tmp.path(), &["rm", &drop]).success();
+    assert!(store.trash_dir().join(&drop).exists());
+
+    fur(tmp.path(), &["undo"]).success();
+    assert!(store.message_exists(&drop));
+    assert!(!store.trash_dir().join(&drop).exists());
+    assert!(store.list_trash().unwrap().is_empty());
+
+    // Redo puts the entry back, restorable as before
+    fur(tmp.path(), &["redo"]).success();
+    assert_eq!(store.list_trash().unwrap().len(), 1);
+    fur(tmp.path(), &["restore", &drop[..8]]).success();
+    assert!(store.message_exists(&drop));
+}
+
+#[test]
+fn thread_archive_and_tag_are_undone_on_their_own() {
+    let tmp = tempdir().unwrap();
+    let store = init_store(tmp.path());
+
+    fur(tmp.path(), &["new", "Kept"]).success();
+    fur(tmp.path(), &["jot", "still here"]).success();
+    let jot = store.load_index().unwrap().current_message.unwrap();
+    let tid = store.load_index().unwrap().active_thread.unwrap();
+
+    fur(tmp.path(), &["thread", "tag", "add", "work"]).success();
+    fur(tmp.path(), &["thread", "archive"]).success();
+    assert!(store.load_thread(&tid).unwrap().archived);
+
+    fur(tmp.path(), &["undo"]).success().stdout(contains("thread archive"));
+    let thread = store.load_thread(&tid).unwrap();
+    assert!(!thread.archived);
+    assert_eq!(thread.tags, ["work"]);
+
+    fur(tmp.path(), &["undo"]).success().stdout(contains("thread tag add"));
+    assert!(store.load_thread(&tid).unwrap().tags.is_empty());
+    assert!(store.message_exists(&jot));
+}
@@ -0,0 +1,67 @@
+use predicates::str::contains;
+use tempfile::tempdir;
+use std::fs;
+use fur_cli::store::FurStore;
+
+mod common;
+use common::{fur, fur_cmd, init_store};
+
+/// Helper: a project with a `.fur` store holding one empty thread
+fn setup_project(tmp: &std::path::Path) -> FurStore {
+    let store = init_store(tmp);
+    fur(tmp, &["new", "Workspace"]).success();
+    store
+}
+
+#[test]
+fn discover_walks_up_to_nearest_store() {
+    let tmp = tempdir().unwrap();
+    let sub = tmp.path().join("notes/deep");
+    fs::create_dir_all(&sub).unwrap();
+    assert_eq!(FurStore::discover(&sub), None);
+
+    setup_project(tmp.path());
+    assert_eq!(FurStore::discover(&sub), Some(tmp.path().join(".fur")));
+}
+
+#[test]
+fn commands_find_store_from_subfolder() {
+    let tmp = tempdir().unwrap();
+    let store = setup_project(tmp.path());
+    let sub = tmp.path().join("docs");
+    fs::create_dir_all(&sub).unwrap();
+    fs::write(sub.join("idea.md"), "# Idea").unwrap();
+
+    fur(&sub, &["jot", "--markdown", "idea.md"])
+        .success()
+        .stdout(contains("✍️ Message jotted down"));
+
+    // The markdown link is recorded relative to the project, not docs/
+    let index = store.load_index().unwrap();
+    let msg = store.load_message(index.current_message.as_deref().unwrap()).unwrap();
+    assert_eq!(msg.markdown.as_deref(), Some("docs/idea.md"));
+    assert!(!sub.join(".fur").exists());
+}
+
+#[test]
+fn fur_dir_flag_and_env_override_discovery() {
+    let tmp = tempdir().unwrap();
+    let elsewhere = tempdir().unwrap();
+    let store = setup_project(tmp.path());
+
+    fur_cmd(elsewhere.path())
+        .args(["jot", "from the flag", "--fur-dir"])
+        .arg(store.root())
+        .assert()
+        .success();
+
+    fur_cmd(elsewhere.path())
+        .env("FUR_DIR", store.root())
+        .args(["jot", "from the env"])
+        .assert()
+        .success();
+
+    let thread = store.active_thread(&store.load_index().unwrap()).unwrap();
+    assert_eq!(thread.messages.len(), 2);
+    assert!(!elsewhere.path().join(".fur").exists());
+}